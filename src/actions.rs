@@ -0,0 +1,250 @@
+//! Input actions and their bindings.
+//!
+//! Gameplay and menus read `ActionState` instead of the keyboard. It is
+//! filled in `CoreStage::PreUpdate` from the keys and gamepad buttons in
+//! `Bindings`, and from the left stick of any connected gamepad, unless
+//! `ActionInput::Scripted` hands it over to something else, like a replay.
+
+use std::collections::{BTreeMap, HashSet};
+use std::{fs, io, path::PathBuf};
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far a stick has to be pushed to count as a move.
+const STICK_DEADZONE: f32 = 0.5;
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        // headless games bring their own bindings
+        if !app.world.contains_resource::<Bindings>() {
+            app.insert_resource(Bindings::load());
+        }
+        app.init_resource::<ActionState>()
+            .init_resource::<ActionInput>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Run,
+    Pause,
+    Confirm,
+    ToggleCamera,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Run,
+        Action::Pause,
+        Action::Confirm,
+        Action::ToggleCamera,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MoveLeft => "Move left",
+            Self::MoveRight => "Move right",
+            Self::MoveUp => "Move up",
+            Self::MoveDown => "Move down",
+            Self::Run => "Run",
+            Self::Pause => "Pause",
+            Self::Confirm => "Confirm",
+            Self::ToggleCamera => "Toggle camera",
+        }
+    }
+}
+
+/// Keys and gamepad buttons of every action, read from
+/// `<config dir>/pac-human/bindings.ron`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Bindings {
+    pub keys: BTreeMap<Action, Vec<KeyCode>>,
+    pub buttons: BTreeMap<Action, Vec<GamepadButtonType>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadButtonType::*;
+        use KeyCode::*;
+
+        let keys = BTreeMap::from([
+            (Action::MoveLeft, vec![Left, A, H]),
+            (Action::MoveRight, vec![Right, D, L]),
+            (Action::MoveUp, vec![Up, W, K]),
+            (Action::MoveDown, vec![Down, S, J]),
+            (Action::Run, vec![Space, LShift]),
+            (Action::Pause, vec![P]),
+            (Action::Confirm, vec![Return, Space]),
+            (Action::ToggleCamera, vec![V]),
+        ]);
+        let buttons = BTreeMap::from([
+            (Action::MoveLeft, vec![DPadLeft]),
+            (Action::MoveRight, vec![DPadRight]),
+            (Action::MoveUp, vec![DPadUp]),
+            (Action::MoveDown, vec![DPadDown]),
+            (Action::Run, vec![West, RightTrigger]),
+            (Action::Pause, vec![Start]),
+            (Action::Confirm, vec![South]),
+            (Action::ToggleCamera, vec![Select]),
+        ]);
+        Self { keys, buttons }
+    }
+}
+
+impl Bindings {
+    /// Reads the bindings file, falling back to the defaults when it is
+    /// missing or unreadable.
+    pub fn load() -> Self {
+        let path = bindings_path();
+        match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("ignoring key bindings in {}: {}", path.display(), err);
+                Self::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                warn!("could not read {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = bindings_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents =
+            ron::ser::to_string_pretty(self, Default::default()).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn buttons(&self, action: Action) -> &[GamepadButtonType] {
+        self.buttons.get(&action).map_or(&[], Vec::as_slice)
+    }
+}
+
+fn bindings_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pac-human")
+        .join("bindings.ron")
+}
+
+/// Where `ActionState` comes from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ActionInput {
+    /// The keyboard and gamepads.
+    #[default]
+    Devices,
+    /// Some other system calls `ActionState::set_pressed` every frame.
+    Scripted,
+}
+
+/// Actions held down this frame.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Every action held down, in the order of `Action::ALL`.
+    pub fn pressed_actions(&self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.pressed(*action))
+            .collect()
+    }
+
+    pub fn any_pressed(&self, actions: impl IntoIterator<Item = Action>) -> bool {
+        actions.into_iter().any(|action| self.pressed(action))
+    }
+
+    /// Replaces the actions held down; the ones that were not held on the
+    /// previous call become just pressed.
+    pub fn set_pressed(&mut self, pressed: HashSet<Action>) {
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.pressed = pressed;
+    }
+}
+
+pub fn update_actions(
+    input: Res<ActionInput>,
+    bindings: Res<Bindings>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<ActionState>,
+) {
+    if *input == ActionInput::Scripted {
+        return;
+    }
+    let mut pressed = HashSet::new();
+    for action in Action::ALL {
+        if keyboard.any_pressed(bindings.keys(action).iter().copied()) {
+            pressed.insert(action);
+        }
+        for gamepad in gamepads.iter() {
+            let buttons = bindings
+                .buttons(action)
+                .iter()
+                .map(|button| GamepadButton::new(*gamepad, *button));
+            if gamepad_buttons.any_pressed(buttons) {
+                pressed.insert(action);
+            }
+        }
+    }
+
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(*gamepad, axis_type))
+                .unwrap_or(0.)
+        };
+        let (x, y) = (
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if x < -STICK_DEADZONE {
+            pressed.insert(Action::MoveLeft);
+        }
+        if x > STICK_DEADZONE {
+            pressed.insert(Action::MoveRight);
+        }
+        if y > STICK_DEADZONE {
+            pressed.insert(Action::MoveUp);
+        }
+        if y < -STICK_DEADZONE {
+            pressed.insert(Action::MoveDown);
+        }
+    }
+
+    actions.set_pressed(pressed);
+}
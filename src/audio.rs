@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use crate::collision::CollisionEvent;
+
+/// Plays the game's sound effects.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_sounds)
+            .add_system_to_stage(CoreStage::PostUpdate, play_collision_sound);
+    }
+}
+
+#[derive(Default)]
+pub struct CollisionSound(pub Handle<AudioSource>);
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CollisionSound(
+        asset_server.load("sounds/breakout_collision.ogg"),
+    ));
+}
+
+pub fn play_collision_sound(
+    collision_events: EventReader<CollisionEvent>,
+    audio: Res<Audio>,
+    sound: Res<CollisionSound>,
+) {
+    if !collision_events.is_empty() {
+        audio.play(sound.0.clone());
+    }
+}
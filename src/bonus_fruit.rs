@@ -0,0 +1,144 @@
+//! The bonus fruit of packman2.
+//!
+//! After `BonusFruitSettings::pellets` pellets of a level have been eaten, the
+//! level's `BonusFruit` shows up on the player's start cell for `secs`
+//! seconds. The fruit is the classic table, from the cherry on level 1 to the
+//! key from level 13 on, and every one collected is kept in
+//! `CollectedFruit` for the HUD.
+
+use bevy::prelude::*;
+
+use crate::collision::snake_eating;
+use crate::components::{BonusFruit, Food, Fruit, InGame, Pellet, Points, PowerPellet, Size};
+use crate::level::{CurrentLevel, Level, Tile};
+use crate::scoring::FruitEaten;
+use crate::state::AppState;
+
+pub struct BonusFruitPlugin;
+
+impl Plugin for BonusFruitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BonusFruitSettings>()
+            .init_resource::<CollectedFruit>()
+            .init_resource::<BonusFruitsReleased>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_bonus_fruit))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(collect_fruit.after(snake_eating))
+                    .with_system(release_bonus_fruit.after(snake_eating))
+                    .with_system(expire_bonus_fruit),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver).with_system(forget_collected_fruit),
+            );
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BonusFruitSettings {
+    /// Pellets eaten in a level before each of its bonus fruit shows up.
+    pub pellets: Vec<usize>,
+    /// Seconds a bonus fruit stays on the board.
+    pub secs: f32,
+}
+
+impl Default for BonusFruitSettings {
+    fn default() -> Self {
+        Self {
+            pellets: vec![50, 100],
+            secs: 9.5,
+        }
+    }
+}
+
+/// Bonus fruit eaten in this game, oldest first.
+#[derive(Default)]
+pub struct CollectedFruit(pub Vec<BonusFruit>);
+
+/// How many of `BonusFruitSettings::pellets` the current level has passed.
+#[derive(Default)]
+struct BonusFruitsReleased(usize);
+
+/// Despawns the bonus fruit when it runs out.
+#[derive(Component)]
+struct FruitTimer(Timer);
+
+fn reset_bonus_fruit(mut released: ResMut<BonusFruitsReleased>) {
+    released.0 = 0;
+}
+
+fn release_bonus_fruit(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<BonusFruitSettings>,
+    current_level: Res<CurrentLevel>,
+    level: Option<Res<Level>>,
+    mut released: ResMut<BonusFruitsReleased>,
+    pellets: Query<(), Or<(With<Pellet>, With<PowerPellet>)>>,
+) {
+    let level = match level {
+        Some(level) => level,
+        None => return,
+    };
+    // nothing is left to eat before the level is on the board, or once it
+    // is cleared
+    let left = pellets.iter().count();
+    if left == 0 {
+        return;
+    }
+    let total = level
+        .cells()
+        .filter(|(_, tile)| matches!(tile, Tile::Pellet | Tile::PowerPellet))
+        .count();
+    let eaten = total.saturating_sub(left);
+    let due = settings
+        .pellets
+        .iter()
+        .filter(|&&pellets| eaten >= pellets)
+        .count();
+    if due <= released.0 {
+        return;
+    }
+    released.0 = due;
+
+    let fruit = BonusFruit::for_level(current_level.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: fruit.color(),
+                ..default()
+            },
+            texture: asset_server.load("images/fruit.png"),
+            ..default()
+        })
+        .insert(fruit)
+        .insert(FruitTimer(Timer::from_seconds(settings.secs, false)))
+        .insert(Food)
+        .insert(Fruit)
+        .insert(InGame)
+        .insert(Points(fruit.points()))
+        .insert(level.player_start().unwrap())
+        .insert(Size::square(0.8));
+}
+
+fn expire_bonus_fruit(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut FruitTimer)>,
+) {
+    for (entity, mut timer) in query.iter_mut() {
+        if timer.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn collect_fruit(mut fruit_events: EventReader<FruitEaten>, mut collected: ResMut<CollectedFruit>) {
+    for fruit in fruit_events.iter().filter_map(|event| event.0) {
+        collected.0.push(fruit);
+    }
+}
+
+fn forget_collected_fruit(mut collected: ResMut<CollectedFruit>) {
+    collected.0.clear();
+}
@@ -0,0 +1,144 @@
+//! The fox's camera.
+//!
+//! By default the camera trails the fox from behind and above, easing
+//! towards its spot instead of sticking to it. `Action::ToggleCamera` swaps
+//! it for the fixed overview of the whole arena and back, and dragging with
+//! the right mouse button orbits the camera around the fox.
+
+use bevy::{input::mouse::MouseMotion, prelude::*, transform::TransformSystem};
+
+use crate::actions::{Action, ActionState};
+use crate::components::Player;
+
+/// Where the overview camera looks at the arena from.
+const OVERVIEW_POSITION: Vec3 = Vec3::new(0., 500., 1000.);
+/// Radians of orbit per pixel of mouse movement.
+const ORBIT_PER_PIXEL: f32 = 0.005;
+
+pub struct FoxCameraPlugin;
+
+impl Plugin for FoxCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FollowCameraSettings>()
+            .init_resource::<CameraView>()
+            .add_startup_system(spawn_camera)
+            .add_system(toggle_camera_view)
+            .add_system(orbit_camera)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                follow_fox.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// How the follow camera trails the fox. Insert it before the plugin to
+/// change the defaults.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FollowCameraSettings {
+    /// How far behind the fox the camera stays.
+    pub distance: f32,
+    /// How far above the ground the camera stays.
+    pub height: f32,
+    /// How quickly the camera catches up; higher is stiffer.
+    pub damping: f32,
+    /// Whether dragging with the right mouse button orbits the camera.
+    pub mouse_orbit: bool,
+}
+
+impl Default for FollowCameraSettings {
+    fn default() -> Self {
+        Self {
+            distance: 300.,
+            height: 200.,
+            damping: 5.,
+            mouse_orbit: true,
+        }
+    }
+}
+
+/// What the camera shows.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CameraView {
+    /// Behind the fox, turned `orbit` radians around it.
+    Follow { orbit: f32 },
+    /// The whole arena from a fixed spot.
+    Overview,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        Self::Follow { orbit: 0. }
+    }
+}
+
+#[derive(Component)]
+pub struct FoxCamera;
+
+fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(Camera3dBundle {
+            transform: overview_transform(),
+            ..default()
+        })
+        .insert(FoxCamera);
+}
+
+fn overview_transform() -> Transform {
+    Transform::from_translation(OVERVIEW_POSITION).looking_at(Vec3::ZERO, Vec3::Y)
+}
+
+fn toggle_camera_view(actions: Res<ActionState>, mut view: ResMut<CameraView>) {
+    if actions.just_pressed(Action::ToggleCamera) {
+        *view = match *view {
+            CameraView::Follow { .. } => CameraView::Overview,
+            CameraView::Overview => CameraView::default(),
+        };
+    }
+}
+
+fn orbit_camera(
+    settings: Res<FollowCameraSettings>,
+    buttons: Res<Input<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut view: ResMut<CameraView>,
+) {
+    let dragged: f32 = motion_events.iter().map(|motion| motion.delta.x).sum();
+    if !settings.mouse_orbit || !buttons.pressed(MouseButton::Right) || dragged == 0. {
+        return;
+    }
+    if let CameraView::Follow { orbit } = &mut *view {
+        *orbit -= dragged * ORBIT_PER_PIXEL;
+    }
+}
+
+/// Eases the camera towards where the current view wants it.
+fn follow_fox(
+    time: Res<Time>,
+    settings: Res<FollowCameraSettings>,
+    view: Res<CameraView>,
+    fox_query: Query<&Transform, (With<Player>, Without<FoxCamera>)>,
+    mut camera_query: Query<&mut Transform, With<FoxCamera>>,
+) {
+    let mut camera_transform = match camera_query.get_single_mut() {
+        Ok(transform) => transform,
+        Err(_) => return,
+    };
+    let target = match (*view, fox_query.get_single()) {
+        (CameraView::Follow { orbit }, Ok(fox_transform)) => {
+            // the fox runs towards its back, so its forward points behind it
+            let behind = fox_transform.forward() * Vec3::new(1., 0., 1.);
+            let behind = Quat::from_rotation_y(orbit) * behind.normalize_or_zero();
+            let fox = fox_transform.translation;
+            Transform::from_translation(
+                fox + behind * settings.distance + Vec3::Y * settings.height,
+            )
+            .looking_at(fox, Vec3::Y)
+        }
+        // between rounds there is no fox to follow
+        _ => overview_transform(),
+    };
+
+    let blend = 1. - (-settings.damping * time.delta_seconds()).exp();
+    camera_transform.translation = camera_transform.translation.lerp(target.translation, blend);
+    camera_transform.rotation = camera_transform.rotation.slerp(target.rotation, blend);
+}
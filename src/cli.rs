@@ -0,0 +1,28 @@
+//! Command-line flags shared by the binaries.
+
+/// Value of `--<name> <value>` or `--<name>=<value>` on the command line.
+pub fn flag_value(name: &str) -> Option<String> {
+    find_flag(std::env::args().skip(1), name)
+}
+
+/// Whether `--<name>` is on the command line.
+pub fn has_flag(name: &str) -> bool {
+    let flag = format!("--{}", name);
+    std::env::args().skip(1).any(|arg| arg == flag)
+}
+
+fn find_flag(mut args: impl Iterator<Item = String>, name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(&flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
@@ -0,0 +1,237 @@
+use bevy::{math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide};
+
+use crate::components::{
+    BonusFruit, Collider, Food, Fruit, Obstacle, Player, Points, PowerPellet, Size,
+};
+use crate::grid::Position;
+use crate::level::Level;
+use crate::maze::MAZE_CELL;
+use crate::movement::{move_fox, move_pacman, snake_movement};
+use crate::power::PowerPelletEaten;
+use crate::scoring::{FruitEaten, ScoreEvent};
+use crate::state::AppState;
+use crate::GameMode;
+
+pub struct CollisionPlugin(pub GameMode);
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionEvent>();
+        match self.0 {
+            GameMode::Pacman => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(check_for_collisions.after(move_pacman)),
+                );
+            }
+            GameMode::Fox | GameMode::FoxMaze => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(block_fox.after(move_fox))
+                        .with_system(keep_fox_in_arena.after(block_fox))
+                        .with_system(check_for_collisions_with_fox.after(keep_fox_in_arena)),
+                );
+            }
+            GameMode::Packman2 => {
+                app.add_event::<GrowthEvent>().add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(snake_eating.after(snake_movement)),
+                );
+            }
+        }
+    }
+}
+
+/// Width and depth of the ground the fox walks on.
+pub const ARENA_SIZE: f32 = 1000.;
+
+/// Sent whenever the player touches something it picks up.
+#[derive(Default)]
+pub struct CollisionEvent;
+
+/// Sent whenever the snake eats, to grow its tail.
+#[derive(Default)]
+pub struct GrowthEvent;
+
+pub fn check_for_collisions(
+    mut commands: Commands,
+    pacman_query: Query<&Transform, With<Player>>,
+    food_query: Query<(Entity, &Transform, &Size, &Points, Option<&PowerPellet>), With<Food>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut pellet_events: EventWriter<PowerPelletEaten>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    let pacman_transform = pacman_query.single();
+    for (food_entity, food_transform, food_size, points, power_pellet) in &food_query {
+        let collision = collide(
+            pacman_transform.translation,
+            Vec2::splat(50.),
+            food_transform.translation,
+            food_size.0.truncate(),
+        );
+
+        if collision.is_some() {
+            collision_events.send_default();
+            commands.entity(food_entity).despawn();
+            score_events.send(ScoreEvent { points: points.0 });
+            if power_pellet.is_some() {
+                pellet_events.send_default();
+            }
+        }
+    }
+}
+
+/// A rectangle on the ground plane, possibly rotated, used to collide the
+/// fox with the things around it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GroundBox {
+    pub center: Vec2,
+    /// Half the width and depth along `axes`.
+    pub half_size: Vec2,
+    /// Unit width and depth directions of the box.
+    pub axes: [Vec2; 2],
+}
+
+impl GroundBox {
+    /// The box `size` wide and deep around `center`, lined up with the x
+    /// and z axes.
+    pub fn axis_aligned(center: Vec2, size: Vec2) -> Self {
+        Self {
+            center,
+            half_size: size / 2.,
+            axes: [Vec2::X, Vec2::Y],
+        }
+    }
+
+    /// The ground an entity of `size` covers, turned the way `transform`
+    /// faces.
+    pub fn of(transform: &Transform, size: &Size) -> Self {
+        let width_axis = transform.right().xz().try_normalize().unwrap_or(Vec2::X);
+        Self {
+            center: transform.translation.xz(),
+            half_size: size.0.xz() / 2.,
+            axes: [width_axis, width_axis.perp()],
+        }
+    }
+
+    /// Half the length of the box's shadow on `axis`.
+    pub fn reach(&self, axis: Vec2) -> f32 {
+        self.half_size.x * self.axes[0].dot(axis).abs()
+            + self.half_size.y * self.axes[1].dot(axis).abs()
+    }
+
+    /// How far this box has to move to stop overlapping `other`, pushing it
+    /// out along the axis where they overlap the least.
+    pub fn push_out(&self, other: &GroundBox) -> Option<Vec2> {
+        let offset = self.center - other.center;
+        let mut push: Option<Vec2> = None;
+        for axis in self.axes.into_iter().chain(other.axes) {
+            let distance = offset.dot(axis);
+            let overlap = self.reach(axis) + other.reach(axis) - distance.abs();
+            if overlap <= 0. {
+                return None;
+            }
+            if push.is_none_or(|push| overlap < push.length()) {
+                let sign = if distance < 0. { -1. } else { 1. };
+                push = Some(axis * sign * overlap);
+            }
+        }
+        push
+    }
+
+    pub fn overlaps(&self, other: &GroundBox) -> bool {
+        self.push_out(other).is_some()
+    }
+}
+
+/// Pushes the fox back out of obstacles, so that walking into a wall at an
+/// angle slides along it.
+pub fn block_fox(
+    mut fox_query: Query<(&mut Transform, &Size), With<Player>>,
+    obstacle_query: Query<(&Transform, &Size), (With<Obstacle>, Without<Player>)>,
+) {
+    let (mut fox_transform, fox_size) = fox_query.single_mut();
+    for (obstacle_transform, obstacle_size) in &obstacle_query {
+        let fox_box = GroundBox::of(&fox_transform, fox_size);
+        let obstacle_box = GroundBox::of(obstacle_transform, obstacle_size);
+        if let Some(push) = fox_box.push_out(&obstacle_box) {
+            fox_transform.translation.x += push.x;
+            fox_transform.translation.z += push.y;
+        }
+    }
+}
+
+/// Keeps the whole fox on the ground plane, or inside the maze's `Level`.
+pub fn keep_fox_in_arena(
+    level: Option<Res<Level>>,
+    mut fox_query: Query<(&mut Transform, &Size), With<Player>>,
+) {
+    let (mut fox_transform, fox_size) = fox_query.single_mut();
+    let fox_box = GroundBox::of(&fox_transform, fox_size);
+    let ground = match level {
+        Some(level) => Vec2::new(level.width() as f32, level.height() as f32) * MAZE_CELL,
+        None => Vec2::splat(ARENA_SIZE),
+    };
+    let limit = ground / 2. - Vec2::new(fox_box.reach(Vec2::X), fox_box.reach(Vec2::Y));
+    let translation = &mut fox_transform.translation;
+    translation.x = translation.x.clamp(-limit.x, limit.x);
+    translation.z = translation.z.clamp(-limit.y, limit.y);
+}
+
+pub fn check_for_collisions_with_fox(
+    mut commands: Commands,
+    fox_query: Query<(&Transform, &Size), With<Player>>,
+    collider_query: Query<(Entity, &Transform, &Size, &Points), With<Collider>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    let (fox_transform, fox_size) = fox_query.single();
+    let fox_box = GroundBox::of(fox_transform, fox_size);
+    for (collider_entity, collider_transform, collider_size, points) in &collider_query {
+        let collider_box = GroundBox::of(collider_transform, collider_size);
+        if fox_box.overlaps(&collider_box) {
+            commands.entity(collider_entity).despawn();
+            collision_events.send_default();
+            score_events.send(ScoreEvent { points: points.0 });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn snake_eating(
+    mut commands: Commands,
+    mut growth_writer: EventWriter<GrowthEvent>,
+    food_positions: Query<
+        (
+            Entity,
+            &Position,
+            &Points,
+            Option<&PowerPellet>,
+            Option<&Fruit>,
+            Option<&BonusFruit>,
+        ),
+        With<Food>,
+    >,
+    head_positions: Query<&Position, With<Player>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut pellet_events: EventWriter<PowerPelletEaten>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut fruit_events: EventWriter<FruitEaten>,
+) {
+    for head_pos in head_positions.iter() {
+        for (ent, food_pos, points, power_pellet, fruit, bonus) in food_positions.iter() {
+            if food_pos == head_pos {
+                commands.entity(ent).despawn();
+                collision_events.send_default();
+                score_events.send(ScoreEvent { points: points.0 });
+                if power_pellet.is_some() {
+                    pellet_events.send_default();
+                }
+                if fruit.is_some() {
+                    fruit_events.send(FruitEaten(bonus.copied()));
+                }
+                growth_writer.send_default();
+            }
+        }
+    }
+}
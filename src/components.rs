@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+/// The actor controlled by the player: pacman, the fox or the snake head.
+#[derive(Component)]
+pub struct Player;
+
+#[derive(Component)]
+pub struct Enemy;
+
+#[derive(Component)]
+pub struct Food;
+
+/// A bonus item that counts towards the fruit collected.
+#[derive(Component)]
+pub struct Fruit;
+
+/// The bonus fruit of a level, with the sprite tint and points of each.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BonusFruit {
+    Cherry,
+    Strawberry,
+    Orange,
+    Apple,
+    Melon,
+    Galaxian,
+    Bell,
+    Key,
+}
+
+impl BonusFruit {
+    pub fn for_level(level: u32) -> Self {
+        match level {
+            0 | 1 => Self::Cherry,
+            2 => Self::Strawberry,
+            3 | 4 => Self::Orange,
+            5 | 6 => Self::Apple,
+            7 | 8 => Self::Melon,
+            9 | 10 => Self::Galaxian,
+            11 | 12 => Self::Bell,
+            _ => Self::Key,
+        }
+    }
+
+    pub fn points(self) -> u32 {
+        match self {
+            Self::Cherry => 100,
+            Self::Strawberry => 300,
+            Self::Orange => 500,
+            Self::Apple => 700,
+            Self::Melon => 1000,
+            Self::Galaxian => 2000,
+            Self::Bell => 3000,
+            Self::Key => 5000,
+        }
+    }
+
+    /// Tint of `images/fruit.png`.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Cherry => Color::RED,
+            Self::Strawberry => Color::PINK,
+            Self::Orange => Color::ORANGE,
+            Self::Apple => Color::LIME_GREEN,
+            Self::Melon => Color::SEA_GREEN,
+            Self::Galaxian => Color::BLUE,
+            Self::Bell => Color::YELLOW,
+            Self::Key => Color::CYAN,
+        }
+    }
+}
+
+/// Score for eating or picking up this entity.
+#[derive(Component, Clone, Copy)]
+pub struct Points(pub u32);
+
+/// A dot in the maze; pellets are also `Food`.
+#[derive(Component)]
+pub struct Pellet;
+
+/// Frightens the enemies when eaten.
+#[derive(Component)]
+pub struct PowerPellet;
+
+/// Belongs to the round being played; despawned when the round is over.
+#[derive(Component)]
+pub struct InGame;
+
+/// Something the fox picks up when it touches it.
+#[derive(Component)]
+pub struct Collider;
+
+/// Something solid the fox cannot walk through.
+#[derive(Component)]
+pub struct Obstacle;
+
+/// Extent of an entity used for collision, or for sprite scaling on the grid.
+#[derive(Component, Clone, Copy)]
+pub struct Size(pub Vec3);
+
+impl Size {
+    pub fn square(x: f32) -> Self {
+        Self(Vec3::splat(x))
+    }
+}
@@ -0,0 +1,155 @@
+//! Menu for rebinding the keyboard keys of each action.
+//!
+//! Opened with C from the title or pause screen. Choosing an action waits for
+//! the next key press and binds the action to that key alone; the bindings
+//! are saved when leaving the menu.
+
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionState, Bindings};
+use crate::screens::{despawn_with, overlay, text, SELECTED_COLOR, UNSELECTED_COLOR};
+use crate::state::AppState;
+
+/// Key opening the menu; it is not an action so that it cannot be unbound.
+const CONTROLS_KEY: KeyCode = KeyCode::C;
+const WAITING_COLOR: Color = Color::ORANGE;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Title).with_system(open_controls))
+            .add_system_set(SystemSet::on_update(AppState::Paused).with_system(open_controls))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Controls).with_system(spawn_controls_screen),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Controls).with_system(controls_menu))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Controls)
+                    .with_system(despawn_with::<ControlsScreen>)
+                    .with_system(save_bindings),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct ControlsScreen;
+
+/// Entries of the menu, top to bottom.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlsEntry {
+    Bind(Action),
+    Defaults,
+    Back,
+}
+
+impl ControlsEntry {
+    fn all() -> Vec<ControlsEntry> {
+        Action::ALL
+            .into_iter()
+            .map(ControlsEntry::Bind)
+            .chain([ControlsEntry::Defaults, ControlsEntry::Back])
+            .collect()
+    }
+
+    fn label(self, bindings: &Bindings) -> String {
+        match self {
+            Self::Bind(action) => {
+                let keys: Vec<String> = bindings
+                    .keys(action)
+                    .iter()
+                    .map(|key| format!("{:?}", key))
+                    .collect();
+                format!("{}: {}", action.label(), keys.join(", "))
+            }
+            Self::Defaults => "Reset to defaults".to_string(),
+            Self::Back => "Back".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ControlsMenu {
+    selected: usize,
+    /// Waiting for the key to bind to the selected action.
+    waiting: bool,
+}
+
+fn open_controls(keyboard: Res<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keyboard.just_pressed(CONTROLS_KEY) {
+        let _ = state.push(AppState::Controls);
+    }
+}
+
+fn spawn_controls_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<Bindings>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.insert_resource(ControlsMenu::default());
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.9)))
+        .insert(ControlsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("CONTROLS", &font, 60., Color::YELLOW));
+            for entry in ControlsEntry::all() {
+                parent
+                    .spawn_bundle(text(entry.label(&bindings), &font, 30., UNSELECTED_COLOR))
+                    .insert(entry);
+            }
+        });
+}
+
+fn controls_menu(
+    keyboard: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
+    mut bindings: ResMut<Bindings>,
+    mut menu: ResMut<ControlsMenu>,
+    mut state: ResMut<State<AppState>>,
+    mut entries: Query<(&ControlsEntry, &mut Text)>,
+) {
+    let all = ControlsEntry::all();
+    let current = all[menu.selected];
+    if menu.waiting {
+        if let (ControlsEntry::Bind(action), Some(key)) =
+            (current, keyboard.get_just_pressed().next())
+        {
+            bindings.keys.insert(action, vec![*key]);
+            menu.waiting = false;
+        }
+    } else {
+        if actions.just_pressed(Action::MoveUp) {
+            menu.selected = (menu.selected + all.len() - 1) % all.len();
+        }
+        if actions.just_pressed(Action::MoveDown) {
+            menu.selected = (menu.selected + 1) % all.len();
+        }
+        if actions.just_pressed(Action::Confirm) {
+            match current {
+                ControlsEntry::Bind(_) => menu.waiting = true,
+                ControlsEntry::Defaults => *bindings = Bindings::default(),
+                ControlsEntry::Back => {
+                    let _ = state.pop();
+                }
+            }
+        }
+    }
+
+    let selected = all[menu.selected];
+    for (entry, mut text) in entries.iter_mut() {
+        let section = &mut text.sections[0];
+        section.value = entry.label(&bindings);
+        section.style.color = match (*entry == selected, menu.waiting) {
+            (true, true) => WAITING_COLOR,
+            (true, false) => SELECTED_COLOR,
+            (false, _) => UNSELECTED_COLOR,
+        };
+    }
+}
+
+fn save_bindings(bindings: Res<Bindings>) {
+    if let Err(err) = bindings.save() {
+        warn!("could not save the key bindings: {}", err);
+    }
+}
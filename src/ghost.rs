@@ -0,0 +1,419 @@
+//! Ghost behaviour on the `Position` grid.
+//!
+//! Every ghost picks a target cell each step and, at each cell, turns towards
+//! whichever open neighbour is closest to it, never reversing on its own. The
+//! target depends on the ghost's `Personality` and on the global
+//! `GhostSchedule`, which alternates between scattering to the corners and
+//! chasing the player. `Frightened` ghosts ignore both and wander at random.
+//!
+//! Pacman has no maze, so its enemies head straight for the same targets,
+//! measured in `GRID_CELL`s, and run away from pacman when frightened.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::components::{Enemy, Player};
+use crate::grid::{Direction, Position};
+use crate::level::{Level, Tile};
+use crate::movement::{move_pacman, Heading, GRID_CELL};
+use crate::play_area::PlayArea;
+use crate::power::{Frightened, Respawning};
+use crate::rng::GameRng;
+use crate::speed::{FrightenedSpeed, Speed, StepProgress, TunnelSpeed};
+use crate::state::AppState;
+use crate::GameMode;
+
+pub struct GhostPlugin(pub GameMode);
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostSchedule>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Playing).with_system(reset_ghost_schedule),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing).with_system(update_ghost_schedule),
+            );
+        match self.0 {
+            GameMode::Pacman => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(chase_pacman.after(update_ghost_schedule).after(move_pacman)),
+                );
+            }
+            GameMode::Packman2 | GameMode::FoxMaze => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(ghost_movement.after(update_ghost_schedule)),
+                );
+            }
+            GameMode::Fox => {}
+        }
+    }
+}
+
+/// How a ghost picks its target while chasing.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Personality {
+    /// Heads straight for the player.
+    Chaser,
+    /// Aims four cells ahead of the player to cut them off.
+    Ambusher,
+    /// Aims at the point opposite the chaser across the cell two ahead of the
+    /// player, so that the two close in from both sides.
+    Flanker,
+    /// Chases the player from afar but retreats to its corner when close.
+    Shy,
+}
+
+impl Personality {
+    /// In the order the ghosts leave the pen.
+    pub const ALL: [Personality; 4] = [
+        Personality::Chaser,
+        Personality::Ambusher,
+        Personality::Flanker,
+        Personality::Shy,
+    ];
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Chaser => Color::rgb(1.0, 0.2, 0.2),
+            Self::Ambusher => Color::rgb(1.0, 0.6, 0.8),
+            Self::Flanker => Color::rgb(0.2, 1.0, 1.0),
+            Self::Shy => Color::rgb(1.0, 0.7, 0.3),
+        }
+    }
+
+    /// Corner of the level the ghost heads for while scattering.
+    pub fn scatter_target(self, level: &Level) -> Position {
+        let (left, right) = (0, level.width() - 1);
+        let (bottom, top) = (0, level.height() - 1);
+        match self {
+            Self::Chaser => Position { x: right, y: top },
+            Self::Ambusher => Position { x: left, y: top },
+            Self::Flanker => Position {
+                x: right,
+                y: bottom,
+            },
+            Self::Shy => Position { x: left, y: bottom },
+        }
+    }
+
+    /// Corner of an area `size` across, centred on the origin, that the enemy
+    /// heads for while scattering.
+    pub fn scatter_corner(self, size: Vec2) -> Vec2 {
+        let half = size / 2.;
+        match self {
+            Self::Chaser => Vec2::new(half.x, half.y),
+            Self::Ambusher => Vec2::new(-half.x, half.y),
+            Self::Flanker => Vec2::new(half.x, -half.y),
+            Self::Shy => Vec2::new(-half.x, -half.y),
+        }
+    }
+
+    /// `chase_target` off the grid, for an enemy at `ghost` and a player at
+    /// `player` moving along `facing`. Distances are in `GRID_CELL`s.
+    pub fn chase_point(
+        self,
+        ghost: Vec2,
+        player: Vec2,
+        facing: Vec2,
+        chaser: Option<Vec2>,
+        corner: Vec2,
+    ) -> Vec2 {
+        match self {
+            Self::Chaser => player,
+            Self::Ambusher => player + facing * 4. * GRID_CELL,
+            Self::Flanker => {
+                let pivot = player + facing * 2. * GRID_CELL;
+                2. * pivot - chaser.unwrap_or(ghost)
+            }
+            Self::Shy => {
+                if ghost.distance(player) > 8. * GRID_CELL {
+                    player
+                } else {
+                    corner
+                }
+            }
+        }
+    }
+
+    /// Target while chasing a player standing at `player` and facing `facing`.
+    pub fn chase_target(
+        self,
+        ghost: Position,
+        player: Position,
+        facing: Direction,
+        chaser: Option<Position>,
+        level: &Level,
+    ) -> Position {
+        match self {
+            Self::Chaser => player,
+            Self::Ambusher => ahead(player, facing, 4),
+            Self::Flanker => {
+                let pivot = ahead(player, facing, 2);
+                let chaser = chaser.unwrap_or(ghost);
+                Position {
+                    x: 2 * pivot.x - chaser.x,
+                    y: 2 * pivot.y - chaser.y,
+                }
+            }
+            Self::Shy => {
+                if distance_squared(ghost, player) > 8 * 8 {
+                    player
+                } else {
+                    self.scatter_target(level)
+                }
+            }
+        }
+    }
+}
+
+/// Global phase all non-frightened ghosts follow.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GhostMode {
+    Scatter,
+    Chase,
+}
+
+/// Alternates scatter and chase phases; the last phase lasts forever.
+///
+/// The schedule is paused while any ghost is frightened.
+pub struct GhostSchedule {
+    phases: Vec<(GhostMode, f32)>,
+    index: usize,
+    timer: Timer,
+    /// Set when the phase changes, so that every ghost turns around on its
+    /// next step.
+    reverse_pending: bool,
+}
+
+impl GhostSchedule {
+    pub fn new(phases: Vec<(GhostMode, f32)>) -> Self {
+        let timer = Timer::from_seconds(phases.first().map_or(0., |phase| phase.1), false);
+        Self {
+            phases,
+            index: 0,
+            timer,
+            reverse_pending: false,
+        }
+    }
+
+    pub fn mode(&self) -> GhostMode {
+        self.phases
+            .get(self.index)
+            .map_or(GhostMode::Chase, |phase| phase.0)
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        if self.index + 1 >= self.phases.len() {
+            return;
+        }
+        if self.timer.tick(delta).just_finished() {
+            self.index += 1;
+            self.timer = Timer::from_seconds(self.phases[self.index].1, false);
+            self.reverse_pending = true;
+        }
+    }
+}
+
+impl Default for GhostSchedule {
+    fn default() -> Self {
+        Self::new(vec![
+            (GhostMode::Scatter, 7.),
+            (GhostMode::Chase, 20.),
+            (GhostMode::Scatter, 7.),
+            (GhostMode::Chase, 20.),
+            (GhostMode::Scatter, 5.),
+            (GhostMode::Chase, 20.),
+            (GhostMode::Scatter, 5.),
+            (GhostMode::Chase, 0.),
+        ])
+    }
+}
+
+fn ahead(pos: Position, facing: Direction, cells: usize) -> Position {
+    (0..cells).fold(pos, |pos, _| pos.step(facing))
+}
+
+fn distance_squared(a: Position, b: Position) -> i32 {
+    (a.x - b.x).pow(2) + (a.y - b.y).pow(2)
+}
+
+/// Direction a ghost at `pos` moving `current` should take next.
+///
+/// Reversing is only allowed in a dead end. Without a target the ghost turns
+/// at random; otherwise ties are broken in the order up, left, down, right.
+pub fn choose_direction(
+    level: &Level,
+    pos: Position,
+    current: Direction,
+    target: Option<Position>,
+    rng: &mut impl Rng,
+) -> Direction {
+    let options: Vec<Direction> = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+    ]
+    .into_iter()
+    .filter(|dir| *dir != current.opposite() && !level.is_wall(level.step(pos, *dir)))
+    .collect();
+
+    if options.is_empty() {
+        return if level.is_wall(level.step(pos, current.opposite())) {
+            Direction::Stay
+        } else {
+            current.opposite()
+        };
+    }
+
+    match target {
+        Some(target) => *options
+            .iter()
+            .min_by_key(|dir| distance_squared(level.step(pos, **dir), target))
+            .unwrap(),
+        None => *options.choose(rng).unwrap(),
+    }
+}
+
+fn reset_ghost_schedule(mut schedule: ResMut<GhostSchedule>) {
+    *schedule = GhostSchedule::default();
+}
+
+pub fn update_ghost_schedule(
+    time: Res<Time>,
+    mut schedule: ResMut<GhostSchedule>,
+    frightened: Query<(), With<Frightened>>,
+) {
+    if frightened.is_empty() {
+        schedule.tick(time.delta());
+    }
+}
+
+pub fn ghost_movement(
+    time: Res<Time>,
+    level: Res<Level>,
+    mut schedule: ResMut<GhostSchedule>,
+    mut rng: ResMut<GameRng>,
+    player: Query<(&Position, &Direction), (With<Player>, Without<Enemy>)>,
+    mut ghosts: Query<
+        (
+            &mut Position,
+            &mut Direction,
+            &mut StepProgress,
+            &Speed,
+            &FrightenedSpeed,
+            &TunnelSpeed,
+            &Personality,
+            Option<&Frightened>,
+        ),
+        (With<Enemy>, Without<Respawning>),
+    >,
+) {
+    let (player_pos, player_dir) = match player.get_single() {
+        Ok((pos, dir)) => (*pos, *dir),
+        Err(_) => return,
+    };
+    let chaser = ghosts
+        .iter()
+        .find(|(.., personality, _)| **personality == Personality::Chaser)
+        .map(|(pos, ..)| *pos);
+    let reverse = std::mem::take(&mut schedule.reverse_pending);
+
+    for (
+        mut pos,
+        mut direction,
+        mut progress,
+        speed,
+        frightened_speed,
+        tunnel_speed,
+        personality,
+        frightened,
+    ) in ghosts.iter_mut()
+    {
+        if reverse && frightened.is_none() {
+            *direction = direction.opposite();
+        }
+        let cells_per_sec = if frightened.is_some() {
+            frightened_speed.0
+        } else if level.tile(*pos) == Tile::Tunnel {
+            tunnel_speed.0
+        } else {
+            speed.0
+        };
+        for _ in 0..progress.advance(cells_per_sec * time.delta_seconds()) {
+            let target = if frightened.is_some() {
+                None
+            } else {
+                Some(match schedule.mode() {
+                    GhostMode::Scatter => personality.scatter_target(&level),
+                    GhostMode::Chase => {
+                        personality.chase_target(*pos, player_pos, player_dir, chaser, &level)
+                    }
+                })
+            };
+            *direction = choose_direction(&level, *pos, *direction, target, &mut *rng);
+            *pos = level.step(*pos, *direction);
+        }
+    }
+}
+
+/// Moves pacman's enemies straight towards their targets, or away from pacman
+/// while they are frightened, without leaving the play area.
+pub fn chase_pacman(
+    time: Res<Time>,
+    area: Res<PlayArea>,
+    schedule: Res<GhostSchedule>,
+    pacman: Query<(&Transform, &Heading), (With<Player>, Without<Enemy>)>,
+    mut enemies: Query<
+        (
+            &mut Transform,
+            &Speed,
+            &FrightenedSpeed,
+            &Personality,
+            Option<&Frightened>,
+        ),
+        (With<Enemy>, Without<Respawning>),
+    >,
+) {
+    let (pacman_transform, heading) = match pacman.get_single() {
+        Ok(pacman) => pacman,
+        Err(_) => return,
+    };
+    let player = pacman_transform.translation.truncate();
+    let facing = match heading.current {
+        Direction::Left => -Vec2::X,
+        Direction::Right => Vec2::X,
+        Direction::Up => Vec2::Y,
+        Direction::Down => -Vec2::Y,
+        Direction::Stay => Vec2::ZERO,
+    };
+    let size = Vec2::new(area.width, area.height);
+    let chaser = enemies
+        .iter()
+        .find(|(.., personality, _)| **personality == Personality::Chaser)
+        .map(|(transform, ..)| transform.translation.truncate());
+
+    for (mut transform, speed, frightened_speed, personality, frightened) in enemies.iter_mut() {
+        let ghost = transform.translation.truncate();
+        let corner = personality.scatter_corner(size);
+        let (target, pixels_per_sec) = if frightened.is_some() {
+            // anywhere further away from pacman will do
+            (ghost + (ghost - player) * 2., frightened_speed.0)
+        } else {
+            let target = match schedule.mode() {
+                GhostMode::Scatter => corner,
+                GhostMode::Chase => personality.chase_point(ghost, player, facing, chaser, corner),
+            };
+            (target, speed.0)
+        };
+        let to_target = target - ghost;
+        let step = to_target.clamp_length_max(pixels_per_sec * time.delta_seconds());
+        let next = (ghost + step).clamp(-size / 2., size / 2.);
+        transform.translation = next.extend(transform.translation.z);
+    }
+}
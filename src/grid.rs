@@ -0,0 +1,240 @@
+//! The `Position` grid of packman2.
+//!
+//! `GridConfig` is the one place the grid's layout comes from: the window
+//! size of the binary, where every `Position` is drawn, how big sprites with
+//! a `Size` are, and which cells the spawners pick from. The grid takes the
+//! size of the level being played and is fitted into the `PlayArea` with
+//! square tiles, centred, whenever the window is resized.
+//!
+//! Actors that can run into each other also keep their `PreviousPosition`, so
+//! that two of them swapping cells in the same frame still `meet`.
+
+use bevy::prelude::*;
+
+use crate::components::Size;
+use crate::play_area::{follow_window, PlayArea};
+
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridConfig>()
+            .add_system_to_stage(CoreStage::PreUpdate, fit_grid.after(follow_window))
+            .add_system_to_stage(CoreStage::PreUpdate, remember_positions)
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_system(position_translation)
+                    .with_system(size_scaling),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridConfig {
+    pub columns: u32,
+    pub rows: u32,
+    /// Width and height of a cell, in pixels.
+    pub tile_size: f32,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            columns: 20,
+            rows: 20,
+            tile_size: 30.,
+        }
+    }
+}
+
+impl GridConfig {
+    /// Width of the whole grid, in pixels.
+    pub fn width(&self) -> f32 {
+        self.columns as f32 * self.tile_size
+    }
+
+    /// Height of the whole grid, in pixels.
+    pub fn height(&self) -> f32 {
+        self.rows as f32 * self.tile_size
+    }
+
+    /// Centre of `pos`, with the grid centred on the origin.
+    pub fn translation(&self, pos: Position) -> Vec2 {
+        Vec2::new(
+            (pos.x as f32 + 0.5 - self.columns as f32 / 2.) * self.tile_size,
+            (pos.y as f32 + 0.5 - self.rows as f32 / 2.) * self.tile_size,
+        )
+    }
+
+    /// Makes the tiles as big as fits in `area`.
+    pub fn fit(&mut self, area: &PlayArea) {
+        self.tile_size = (area.width / self.columns as f32).min(area.height / self.rows as f32);
+    }
+}
+
+pub const WALL_COLOR: Color = Color::rgb(0.5, 0.0, 1.0);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Cell a grid actor was on at the start of the frame.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreviousPosition(pub Position);
+
+/// Whether actors now at `a` and `b`, coming from `a_from` and `b_from`,
+/// ended up on the same cell or passed through each other head-on.
+pub fn meet(a: Position, a_from: Position, b: Position, b_from: Position) -> bool {
+    a == b || (a == b_from && b == a_from)
+}
+
+/// The direction a grid actor moves in every tick.
+#[derive(Component, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+    Stay,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Stay => Self::Stay,
+        }
+    }
+}
+
+impl Position {
+    /// The neighbouring cell in `direction`.
+    pub fn step(self, direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Self {
+                x: self.x - 1,
+                ..self
+            },
+            Direction::Right => Self {
+                x: self.x + 1,
+                ..self
+            },
+            Direction::Up => Self {
+                y: self.y + 1,
+                ..self
+            },
+            Direction::Down => Self {
+                y: self.y - 1,
+                ..self
+            },
+            Direction::Stay => self,
+        }
+    }
+
+    /// Whether `other` is one step away, not counting wrapping round an edge.
+    pub fn is_next_to(self, other: Self) -> bool {
+        (self.x - other.x).abs() + (self.y - other.y).abs() == 1
+    }
+}
+
+fn remember_positions(mut q: Query<(&Position, &mut PreviousPosition)>) {
+    for (pos, mut previous) in q.iter_mut() {
+        previous.0 = *pos;
+    }
+}
+
+/// Makes the tiles as big as fits in the play area.
+fn fit_grid(area: Res<PlayArea>, mut grid: ResMut<GridConfig>) {
+    let mut fitted = *grid;
+    fitted.fit(&area);
+    if *grid != fitted {
+        *grid = fitted;
+    }
+}
+
+/// Sizes grid sprites; a `Size` of 1 covers exactly one cell.
+pub fn size_scaling(grid: Res<GridConfig>, mut q: Query<(&Size, &mut Sprite), With<Position>>) {
+    for (size, mut sprite) in q.iter_mut() {
+        sprite.custom_size = Some(size.0.truncate() * grid.tile_size);
+    }
+}
+
+pub fn position_translation(grid: Res<GridConfig>, mut q: Query<(&Position, &mut Transform)>) {
+    for (pos, mut transform) in q.iter_mut() {
+        transform.translation = grid.translation(*pos).extend(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(columns: u32, rows: u32) -> GridConfig {
+        GridConfig {
+            columns,
+            rows,
+            tile_size: 10.,
+        }
+    }
+
+    #[test]
+    fn translation_centres_an_odd_grid_on_its_middle_cell() {
+        let grid = grid(3, 5);
+        assert_eq!(grid.translation(Position { x: 1, y: 2 }), Vec2::ZERO);
+        assert_eq!(
+            grid.translation(Position { x: 0, y: 0 }),
+            Vec2::new(-10., -20.)
+        );
+        assert_eq!(
+            grid.translation(Position { x: 2, y: 4 }),
+            Vec2::new(10., 20.)
+        );
+    }
+
+    #[test]
+    fn translation_centres_an_even_grid_between_cells() {
+        let grid = grid(4, 2);
+        assert_eq!(
+            grid.translation(Position { x: 0, y: 0 }),
+            Vec2::new(-15., -5.)
+        );
+        assert_eq!(
+            grid.translation(Position { x: 3, y: 1 }),
+            Vec2::new(15., 5.)
+        );
+        // the middle cells lie around the origin, not on it
+        assert_eq!(
+            grid.translation(Position { x: 1, y: 0 }),
+            Vec2::new(-5., -5.)
+        );
+        assert_eq!(grid.translation(Position { x: 2, y: 1 }), Vec2::new(5., 5.));
+    }
+
+    #[test]
+    fn fit_grid_fills_the_narrower_side_of_the_play_area() {
+        let mut app = App::new();
+        app.insert_resource(PlayArea {
+            width: 800.,
+            height: 300.,
+        })
+        .insert_resource(grid(20, 10))
+        .add_system(fit_grid);
+
+        app.update();
+        // 40 would fit across, but only 30 down
+        assert_eq!(app.world.resource::<GridConfig>().tile_size, 30.);
+
+        app.insert_resource(PlayArea {
+            width: 200.,
+            height: 600.,
+        });
+        app.update();
+        assert_eq!(app.world.resource::<GridConfig>().tile_size, 10.);
+    }
+}
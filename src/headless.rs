@@ -0,0 +1,145 @@
+//! Running a game without a window, for tests and simulations.
+//!
+//! `headless_app` builds an `App` on `MinimalPlugins` with `GameLogicPlugins`,
+//! a fixed time step and a seeded RNG, and lets an `Agent` play it. High
+//! scores and bindings are kept in memory, away from the player's files.
+//! Every `App::update` is one tick:
+//!
+//! ```no_run
+//! use pac_human::actions::Action;
+//! use pac_human::headless::headless_app;
+//! use pac_human::scoring::ScoreBoard;
+//! use pac_human::GameMode;
+//!
+//! let mut app = headless_app(GameMode::Packman2, 0, |_: &mut bevy::prelude::World| {
+//!     vec![Action::MoveLeft]
+//! });
+//! for _ in 0..600 {
+//!     app.update();
+//! }
+//! println!("score: {}", app.world.resource::<ScoreBoard>().score);
+//! ```
+
+use std::time::Duration;
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*, time::TimeSystem};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::actions::{Action, ActionInput, ActionState, Bindings};
+use crate::grid::GridConfig;
+use crate::highscores::HighScores;
+use crate::play_area::PlayArea;
+use crate::rng::GameRng;
+use crate::state::AppState;
+use crate::{GameLogicPlugins, GameMode};
+
+/// Game time of one tick.
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Plays a headless game by choosing the actions held down every tick.
+pub trait Agent: Send + Sync + 'static {
+    fn act(&mut self, world: &mut World) -> Vec<Action>;
+}
+
+impl<F> Agent for F
+where
+    F: FnMut(&mut World) -> Vec<Action> + Send + Sync + 'static,
+{
+    fn act(&mut self, world: &mut World) -> Vec<Action> {
+        self(world)
+    }
+}
+
+/// Never touches the controls.
+pub struct IdleAgent;
+
+impl Agent for IdleAgent {
+    fn act(&mut self, _world: &mut World) -> Vec<Action> {
+        Vec::new()
+    }
+}
+
+/// Holds a random direction, picking a new one every `hold_ticks` ticks.
+pub struct RandomWalkAgent {
+    rng: StdRng,
+    hold_ticks: u32,
+    ticks: u32,
+    current: Action,
+}
+
+impl RandomWalkAgent {
+    pub fn new(seed: u64, hold_ticks: u32) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            hold_ticks: hold_ticks.max(1),
+            ticks: 0,
+            current: Action::MoveUp,
+        }
+    }
+}
+
+impl Agent for RandomWalkAgent {
+    fn act(&mut self, _world: &mut World) -> Vec<Action> {
+        if self.ticks.is_multiple_of(self.hold_ticks) {
+            let moves = [
+                Action::MoveLeft,
+                Action::MoveRight,
+                Action::MoveUp,
+                Action::MoveDown,
+            ];
+            self.current = *moves.choose(&mut self.rng).unwrap();
+        }
+        self.ticks += 1;
+        vec![self.current]
+    }
+}
+
+struct AgentSlot(Box<dyn Agent>);
+
+/// A game of `mode` that starts right away in `AppState::Playing`, advances
+/// by `TICK` on every update and is played by `agent`.
+pub fn headless_app(mode: GameMode, seed: u64, agent: impl Agent) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(InputPlugin)
+        .add_plugin(AssetPlugin)
+        .insert_resource(GameRng::new(seed))
+        .insert_resource(ActionInput::Scripted)
+        .insert_resource(AgentSlot(Box::new(agent)))
+        // keep the player's own files out of headless games
+        .insert_resource(Bindings::default())
+        .insert_resource(HighScores::in_memory());
+    if mode.is_fox() {
+        // the cubes are meshes even though nothing draws them
+        app.add_asset::<Mesh>().add_asset::<StandardMaterial>();
+    }
+    if mode == GameMode::Packman2 {
+        let grid = GridConfig::default();
+        app.insert_resource(PlayArea {
+            width: grid.width(),
+            height: grid.height(),
+        });
+    }
+    app.add_plugins(GameLogicPlugins(mode))
+        // start in `Playing` rather than on the title screen
+        .insert_resource(State::new(AppState::Playing))
+        .add_system_to_stage(CoreStage::First, fixed_tick.after(TimeSystem))
+        .add_system_to_stage(CoreStage::PreUpdate, run_agent.exclusive_system().at_end());
+    app
+}
+
+/// Makes every update last exactly one `TICK`, however long it really took.
+fn fixed_tick(mut time: ResMut<Time>) {
+    if let Some(last_update) = time.last_update() {
+        time.update_with_instant(last_update + TICK);
+    }
+}
+
+fn run_agent(world: &mut World) {
+    world.resource_scope(|world, mut slot: Mut<AgentSlot>| {
+        let actions = slot.0.act(world);
+        world
+            .resource_mut::<ActionState>()
+            .set_pressed(actions.into_iter().collect());
+    });
+}
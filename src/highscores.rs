@@ -0,0 +1,135 @@
+//! Top-10 table of each game, saved under the user data directory.
+//!
+//! Every `GameMode` has its own file, e.g.
+//! `~/.local/share/pac-human/highscores-pacman.ron` on Linux.
+
+use std::{cmp::Reverse, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::scoring::ScoreBoard;
+use crate::GameMode;
+
+/// Number of entries kept in a table.
+pub const TABLE_LEN: usize = 10;
+/// Letters of the initials entered with a high score.
+pub const INITIALS_LEN: usize = 3;
+
+pub struct HighScorePlugin(pub GameMode);
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        // headless games bring their own table
+        if !app.world.contains_resource::<HighScores>() {
+            app.insert_resource(HighScores::load(self.0));
+        }
+        app.add_startup_system(seed_high_score);
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+}
+
+/// High scores of one game, best first.
+pub struct HighScores {
+    /// File the table is saved to; `None` keeps it in memory.
+    path: Option<PathBuf>,
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Reads the table of `mode`. A missing or unreadable file gives an
+    /// empty table.
+    pub fn load(mode: GameMode) -> Self {
+        let path = scores_path(mode);
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("ignoring high scores in {}: {}", path.display(), err);
+                Vec::new()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                warn!("could not read {}: {}", path.display(), err);
+                Vec::new()
+            }
+        };
+        let mut scores = Self {
+            path: Some(path),
+            entries,
+        };
+        scores.entries.sort_by_key(|entry| Reverse(entry.score));
+        scores.entries.truncate(TABLE_LEN);
+        scores
+    }
+
+    /// An empty table that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Writes the table to its file; does nothing for an in-memory table.
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(&self.entries, Default::default())
+            .map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    /// Whether `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < TABLE_LEN
+                || self.entries.last().is_none_or(|last| score > last.score))
+    }
+
+    /// Adds an entry below the ones with the same score and returns its rank,
+    /// starting at 0.
+    pub fn insert(&mut self, initials: impl Into<String>, score: u32) -> usize {
+        let rank = self
+            .entries
+            .iter()
+            .position(|entry| score > entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(
+            rank,
+            HighScoreEntry {
+                initials: initials.into(),
+                score,
+            },
+        );
+        self.entries.truncate(TABLE_LEN);
+        rank
+    }
+}
+
+fn scores_path(mode: GameMode) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pac-human")
+        .join(format!("highscores-{}.ron", mode.name()))
+}
+
+fn seed_high_score(high_scores: Res<HighScores>, mut scoreboard: ResMut<ScoreBoard>) {
+    scoreboard.high_score = scoreboard.high_score.max(high_scores.best());
+}
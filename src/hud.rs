@@ -0,0 +1,180 @@
+//! Heads-up display along the top of the window.
+//!
+//! Shows the score and high score in every game, plus the lives, time left,
+//! level and fruit in the games that have them. The bonus fruit collected in
+//! packman2 are lined up along the bottom.
+
+use bevy::prelude::*;
+
+use crate::bonus_fruit::CollectedFruit;
+use crate::level::CurrentLevel;
+use crate::lives::Lives;
+use crate::scoring::ScoreBoard;
+use crate::time_attack::TimeAttack;
+use crate::GameMode;
+
+const HUD_FONT_SIZE: f32 = 30.;
+const HUD_HEIGHT: f32 = 40.;
+/// Width and height of a fruit in the bottom row.
+const FRUIT_ICON_SIZE: f32 = 30.;
+/// Most recent bonus fruit shown in the bottom row.
+const FRUIT_ICONS: usize = 7;
+
+pub struct HudPlugin(pub GameMode);
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HudLayout {
+            show_level: self.0 != GameMode::Fox,
+            show_fruit: self.0 == GameMode::Packman2,
+        })
+        .add_startup_system(spawn_hud)
+        .add_startup_system(spawn_fruit_row)
+        .add_system_to_stage(CoreStage::PostUpdate, update_hud)
+        .add_system_to_stage(CoreStage::PostUpdate, update_fruit_row);
+    }
+}
+
+struct HudLayout {
+    /// The fox has no levels.
+    show_level: bool,
+    /// Only packman2 has fruit to eat.
+    show_fruit: bool,
+}
+
+#[derive(Component)]
+pub struct HudText;
+
+/// Bottom row of the HUD holding the collected bonus fruit.
+#[derive(Component)]
+pub struct FruitRow;
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: HUD_FONT_SIZE,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: bevy::ui::Size::new(Val::Percent(100.), Val::Px(HUD_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(0.),
+                    left: Val::Px(0.),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                padding: UiRect {
+                    left: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            },
+            color: Color::rgba(0., 0., 0., 0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section("", text_style))
+                .insert(HudText);
+        });
+}
+
+fn update_hud(
+    layout: Res<HudLayout>,
+    scoreboard: Res<ScoreBoard>,
+    current_level: Res<CurrentLevel>,
+    lives: Option<Res<Lives>>,
+    time_attack: Option<Res<TimeAttack>>,
+    mut query: Query<&mut Text, With<HudText>>,
+) {
+    let lives_changed = lives.as_ref().is_some_and(|lives| lives.is_changed());
+    let time_changed = time_attack.as_ref().is_some_and(|run| run.is_changed());
+    if !(scoreboard.is_changed() || current_level.is_changed() || lives_changed || time_changed) {
+        return;
+    }
+
+    let mut value = format!(
+        "Score: {}   High: {}",
+        scoreboard.score, scoreboard.high_score
+    );
+    if let Some(lives) = lives {
+        value += &format!("   Lives: {}", lives.0);
+    }
+    if let Some(run) = time_attack {
+        value += &format!("   Time: {:.1}", run.remaining);
+    }
+    if layout.show_level {
+        value += &format!("   Level: {}", current_level.0);
+    }
+    if layout.show_fruit {
+        value += &format!("   Fruit: {}", scoreboard.fruit);
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn spawn_fruit_row(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: bevy::ui::Size::new(Val::Percent(100.), Val::Px(HUD_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(0.),
+                    left: Val::Px(0.),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::FlexEnd,
+                padding: UiRect {
+                    right: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(FruitRow);
+}
+
+fn update_fruit_row(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    collected: Option<Res<CollectedFruit>>,
+    query: Query<Entity, With<FruitRow>>,
+) {
+    let collected = match collected {
+        Some(collected) if collected.is_changed() => collected,
+        _ => return,
+    };
+    let icon = asset_server.load("images/fruit.png");
+    let shown = &collected.0[collected.0.len().saturating_sub(FRUIT_ICONS)..];
+    for row in query.iter() {
+        commands.entity(row).despawn_descendants();
+        commands.entity(row).with_children(|parent| {
+            for fruit in shown {
+                parent.spawn_bundle(ImageBundle {
+                    style: Style {
+                        size: bevy::ui::Size::new(
+                            Val::Px(FRUIT_ICON_SIZE),
+                            Val::Px(FRUIT_ICON_SIZE),
+                        ),
+                        margin: UiRect {
+                            left: Val::Px(5.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    image: icon.clone().into(),
+                    color: fruit.color().into(),
+                    ..default()
+                });
+            }
+        });
+    }
+}
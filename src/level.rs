@@ -0,0 +1,487 @@
+use std::{collections::HashSet, env, fmt, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::components::{Food, InGame, Pellet, Player, Points, PowerPellet, Size};
+use crate::grid::Direction;
+use crate::grid::{GridConfig, Position, PreviousPosition, WALL_COLOR};
+use crate::play_area::PlayArea;
+use crate::speed::{tune_speeds, LevelSpeeds, Speed, StepProgress};
+use crate::state::AppState;
+
+pub const PELLET_COLOR: Color = Color::rgb(1.0, 0.9, 0.6);
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>().add_system_set(
+            SystemSet::on_enter(AppState::Playing).with_system(load_level.after(tune_speeds)),
+        );
+    }
+}
+
+/// Number of the level being played, starting at 1.
+pub struct CurrentLevel(pub u32);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// One cell of a level map.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Pellet,
+    PowerPellet,
+    PlayerStart,
+    GhostStart,
+    Tunnel,
+}
+
+impl Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            ' ' => Some(Self::Empty),
+            '#' => Some(Self::Wall),
+            '.' => Some(Self::Pellet),
+            'o' => Some(Self::PowerPellet),
+            'P' => Some(Self::PlayerStart),
+            'G' => Some(Self::GhostStart),
+            'T' => Some(Self::Tunnel),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to an actor stepping off the edge of the map.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EdgePolicy {
+    /// The edge is a wall.
+    Solid,
+    /// Every edge leads round to the opposite one, like `warp_pacman`.
+    Wrap,
+    /// Only `Tunnel` cells lead round to the opposite edge.
+    #[default]
+    Tunnels,
+}
+
+impl EdgePolicy {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "solid" => Some(Self::Solid),
+            "wrap" => Some(Self::Wrap),
+            "tunnels" => Some(Self::Tunnels),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    /// The map contains a character that is not a tile.
+    UnknownTile {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// The `edges:` line names no `EdgePolicy`.
+    UnknownEdgePolicy(String),
+    /// The map has no `P` cell.
+    NoPlayerStart,
+    /// An empty line inside the map; an open row is written with spaces.
+    BlankRow {
+        line: usize,
+    },
+    Empty,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::UnknownTile {
+                line,
+                column,
+                found,
+            } => {
+                write!(
+                    f,
+                    "unknown tile {:?} at line {}, column {}",
+                    found, line, column
+                )
+            }
+            Self::UnknownEdgePolicy(name) => write!(
+                f,
+                "unknown edge policy {:?}, expected solid, wrap or tunnels",
+                name
+            ),
+            Self::NoPlayerStart => write!(f, "no player start (P) in level"),
+            Self::BlankRow { line } => write!(f, "blank row at line {}", line),
+            Self::Empty => write!(f, "level is empty"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// A maze read from an ASCII map.
+///
+/// The first line of the map is the top row of the grid, so `Position { x, y }`
+/// is found at line `height - 1 - y`, column `x`. Lines shorter than the
+/// longest one are padded with empty cells. The map may start with a line
+/// such as `edges: wrap` to pick its `EdgePolicy`.
+#[derive(Clone, Debug)]
+pub struct Level {
+    width: i32,
+    height: i32,
+    tiles: Vec<Tile>,
+    edges: EdgePolicy,
+}
+
+impl Level {
+    pub fn parse(map: &str) -> Result<Self, LevelError> {
+        let mut lines: Vec<&str> = map.lines().collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let mut edges = EdgePolicy::default();
+        if let Some(name) = lines.first().and_then(|line| line.strip_prefix("edges:")) {
+            let name = name.trim();
+            edges = EdgePolicy::from_name(name)
+                .ok_or_else(|| LevelError::UnknownEdgePolicy(name.to_string()))?;
+            lines.remove(0);
+        }
+        if let Some(row) = lines.iter().position(|line| line.is_empty()) {
+            return Err(LevelError::BlankRow { line: row + 1 });
+        }
+        let height = lines.len();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(LevelError::Empty);
+        }
+
+        let mut tiles = vec![Tile::Empty; width * height];
+        for (row, line) in lines.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let tile = Tile::from_char(c).ok_or(LevelError::UnknownTile {
+                    line: row + 1,
+                    column: column + 1,
+                    found: c,
+                })?;
+                tiles[(height - 1 - row) * width + column] = tile;
+            }
+        }
+
+        let level = Self {
+            width: width as i32,
+            height: height as i32,
+            tiles,
+            edges,
+        };
+        if level.player_start().is_none() {
+            return Err(LevelError::NoPlayerStart);
+        }
+        Ok(level)
+    }
+
+    /// Reads `assets/levels/level<number>.txt`.
+    pub fn load(number: u32) -> Result<Self, LevelError> {
+        let path = levels_dir().join(format!("level{}.txt", number));
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Like `load`, but starts over from level 1 after the last level file.
+    pub fn load_or_first(number: u32) -> Result<Self, LevelError> {
+        match Self::load(number) {
+            Err(LevelError::Io(err)) if err.kind() == io::ErrorKind::NotFound && number > 1 => {
+                Self::load(1)
+            }
+            result => result,
+        }
+    }
+
+    /// Like `load_or_first`, but plays the built-in copy of level 1 when the
+    /// level cannot be read, rather than stopping the game.
+    pub fn load_for_play(number: u32) -> Self {
+        Self::load_or_first(number).unwrap_or_else(|err| {
+            warn!("failed to load level {}: {}; playing level 1", number, err);
+            Self::parse(BUILT_IN_LEVEL).expect("the built-in level is valid")
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    /// The tile at `pos`, or `Tile::Empty` outside the map.
+    pub fn tile(&self, pos: Position) -> Tile {
+        if self.contains(pos) {
+            self.tiles[(pos.y * self.width + pos.x) as usize]
+        } else {
+            Tile::Empty
+        }
+    }
+
+    pub fn edges(&self) -> EdgePolicy {
+        self.edges
+    }
+
+    /// Cells outside the map are walls too; `step` is how actors get round
+    /// the edges.
+    pub fn is_wall(&self, pos: Position) -> bool {
+        !self.contains(pos) || self.tile(pos) == Tile::Wall
+    }
+
+    /// The cell an actor at `pos` moving in `direction` ends up in. Off the
+    /// edge of the map that is the cell on the opposite edge when the
+    /// `EdgePolicy` allows it, and otherwise a cell outside the map, which
+    /// `is_wall` blocks.
+    pub fn step(&self, pos: Position, direction: Direction) -> Position {
+        let next = pos.step(direction);
+        let wraps = match self.edges {
+            EdgePolicy::Solid => false,
+            EdgePolicy::Wrap => true,
+            EdgePolicy::Tunnels => self.tile(pos) == Tile::Tunnel,
+        };
+        if self.contains(next) || !wraps {
+            return next;
+        }
+        Position {
+            x: next.x.rem_euclid(self.width),
+            y: next.y.rem_euclid(self.height),
+        }
+    }
+
+    /// Every open cell that can be walked to from `start`, nearest first.
+    pub fn reachable_from(&self, start: Position) -> Vec<Position> {
+        if self.is_wall(start) {
+            return Vec::new();
+        }
+        let mut seen = HashSet::from([start]);
+        let mut reachable = vec![start];
+        let mut next = 0;
+        while let Some(&pos) = reachable.get(next) {
+            next += 1;
+            for direction in [
+                Direction::Up,
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+            ] {
+                let neighbour = self.step(pos, direction);
+                if !self.is_wall(neighbour) && seen.insert(neighbour) {
+                    reachable.push(neighbour);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Every cell of the map together with its tile.
+    pub fn cells(&self) -> impl Iterator<Item = (Position, Tile)> + '_ {
+        self.tiles.iter().enumerate().map(|(i, tile)| {
+            let i = i as i32;
+            (
+                Position {
+                    x: i % self.width,
+                    y: i / self.width,
+                },
+                *tile,
+            )
+        })
+    }
+
+    pub fn player_start(&self) -> Option<Position> {
+        self.cells()
+            .find(|(_, tile)| *tile == Tile::PlayerStart)
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn ghost_starts(&self) -> Vec<Position> {
+        self.cells()
+            .filter(|(_, tile)| *tile == Tile::GhostStart)
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+}
+
+/// Level 1, for when the level files are missing or broken.
+const BUILT_IN_LEVEL: &str = include_str!("../assets/levels/level1.txt");
+
+/// `assets/levels`, resolved the same way Bevy resolves its asset folder.
+fn levels_dir() -> PathBuf {
+    let base = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(PathBuf::from))
+        })
+        .unwrap_or_default();
+    base.join("assets").join("levels")
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+/// Spawns the walls, pellets and player of the current level.
+fn load_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    speeds: Res<LevelSpeeds>,
+    area: Res<PlayArea>,
+    mut grid: ResMut<GridConfig>,
+) {
+    let level = Level::load_for_play(current_level.0);
+    grid.columns = level.width() as u32;
+    grid.rows = level.height() as u32;
+    // `fit_grid` only runs again next frame
+    grid.fit(&area);
+
+    for (pos, tile) in level.cells() {
+        match tile {
+            Tile::Wall => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: WALL_COLOR,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(Wall)
+                    .insert(InGame)
+                    .insert(pos)
+                    .insert(Size::square(1.));
+            }
+            Tile::Pellet => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: PELLET_COLOR,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(Pellet)
+                    .insert(Food)
+                    .insert(InGame)
+                    .insert(Points(10))
+                    .insert(pos)
+                    .insert(Size::square(0.2));
+            }
+            Tile::PowerPellet => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: PELLET_COLOR,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(PowerPellet)
+                    .insert(Food)
+                    .insert(InGame)
+                    .insert(Points(50))
+                    .insert(pos)
+                    .insert(Size::square(0.5));
+            }
+            Tile::PlayerStart => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        texture: asset_server.load("images/pacman.png"),
+                        ..default()
+                    })
+                    .insert(Player)
+                    .insert(InGame)
+                    .insert(Direction::Up)
+                    .insert(Speed(speeds.0.snake))
+                    .insert(StepProgress::default())
+                    .insert(pos)
+                    .insert(PreviousPosition(pos))
+                    .insert(Size::square(0.9));
+            }
+            Tile::Empty | Tile::GhostStart | Tile::Tunnel => {}
+        }
+    }
+
+    commands.insert_resource(level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reachable_from_follows_tunnels_and_skips_sealed_pockets() {
+        let level = Level::parse(
+            "edges: tunnels
+#####
+TP#.T
+#####
+#.#.#
+",
+        )
+        .unwrap();
+
+        let reachable: HashSet<Position> = level
+            .reachable_from(level.player_start().unwrap())
+            .into_iter()
+            .collect();
+
+        // the cell right of the wall is only reached through the tunnel
+        let expected = [(1, 2), (0, 2), (4, 2), (3, 2)]
+            .map(|(x, y)| Position { x, y })
+            .into();
+        assert_eq!(reachable, expected);
+    }
+
+    #[test]
+    fn a_blank_row_inside_the_map_is_an_error() {
+        assert!(matches!(
+            Level::parse("#####\n#P..#\n\n#####\n"),
+            Err(LevelError::BlankRow { line: 3 })
+        ));
+        // a row of spaces is an open corridor
+        assert_eq!(
+            Level::parse("#####\n#P..#\n     \n#####\n")
+                .unwrap()
+                .height(),
+            4
+        );
+    }
+
+    #[test]
+    fn blank_lines_after_the_map_are_ignored() {
+        assert_eq!(Level::parse("#P#\n\n\n").unwrap().height(), 1);
+    }
+
+    #[test]
+    fn the_built_in_level_is_valid() {
+        assert!(Level::parse(BUILT_IN_LEVEL).is_ok());
+    }
+}
@@ -0,0 +1,133 @@
+//! Shared game logic for the pac-human binaries.
+//!
+//! The `pacman`, `fox` and `packman2` binaries only set up their window and
+//! scene, and compose the plugins below for everything else.
+
+// queries with filters are how systems say what they run on
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod actions;
+pub mod audio;
+pub mod bonus_fruit;
+pub mod camera;
+pub mod cli;
+pub mod collision;
+pub mod components;
+pub mod controls;
+pub mod ghost;
+pub mod grid;
+pub mod headless;
+pub mod highscores;
+pub mod hud;
+pub mod level;
+pub mod lives;
+pub mod maze;
+pub mod movement;
+pub mod play_area;
+pub mod power;
+pub mod replay;
+pub mod rng;
+pub mod scoring;
+pub mod screens;
+pub mod snake;
+pub mod spawning;
+pub mod speed;
+pub mod state;
+pub mod time_attack;
+
+/// Which of the games a plugin is being added to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    /// 2D game with free movement (`pacman` binary).
+    Pacman,
+    /// 3D game with the fox (`fox` binary).
+    Fox,
+    /// 2D game on the `Position` grid (`packman2` binary).
+    Packman2,
+    /// 3D maze with the fox and ghosts (`fox --maze`).
+    FoxMaze,
+}
+
+impl GameMode {
+    /// Name of the binary, also used for the files saved per game.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pacman => "pacman",
+            Self::Fox => "fox",
+            Self::Packman2 => "packman2",
+            Self::FoxMaze => "fox-maze",
+        }
+    }
+
+    /// Whether the game is played by the fox in 3D.
+    pub fn is_fox(self) -> bool {
+        matches!(self, Self::Fox | Self::FoxMaze)
+    }
+}
+
+/// Adds every shared plugin for `mode`.
+///
+/// Binaries that want to leave one out can add the plugins one by one instead.
+pub struct PacHumanPlugins(pub GameMode);
+
+impl PluginGroup for PacHumanPlugins {
+    fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
+        // the replay has to pick the seed and take over the input first
+        group.add(replay::ReplayPlugin(self.0));
+        GameLogicPlugins(self.0).build(group);
+        group.add(hud::HudPlugin(self.0)).add(audio::SoundPlugin);
+        if self.0.is_fox() {
+            group.add(camera::FoxCameraPlugin);
+        }
+    }
+}
+
+/// The plugins of `PacHumanPlugins` that neither draw nor play anything, and
+/// run without a window; see `headless`.
+pub struct GameLogicPlugins(pub GameMode);
+
+impl PluginGroup for GameLogicPlugins {
+    fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
+        group
+            .add(state::StatePlugin)
+            .add(play_area::PlayAreaPlugin)
+            .add(actions::ActionPlugin)
+            .add(rng::RngPlugin)
+            .add(speed::SpeedPlugin)
+            .add(spawning::SpawningPlugin(self.0))
+            .add(movement::MovementPlugin(self.0))
+            .add(collision::CollisionPlugin(self.0))
+            .add(screens::ScreensPlugin(self.0))
+            .add(controls::ControlsPlugin)
+            .add(scoring::ScoringPlugin)
+            .add(highscores::HighScorePlugin(self.0));
+        match self.0 {
+            GameMode::Pacman => {
+                group
+                    .add(power::PowerPlugin(self.0))
+                    .add(lives::LivesPlugin(self.0))
+                    .add(ghost::GhostPlugin(self.0));
+            }
+            GameMode::Fox => {}
+            GameMode::Packman2 => {
+                group
+                    .add(power::PowerPlugin(self.0))
+                    .add(lives::LivesPlugin(self.0))
+                    .add(grid::GridPlugin)
+                    .add(level::LevelPlugin)
+                    .add(ghost::GhostPlugin(self.0))
+                    .add(snake::SnakePlugin)
+                    .add(bonus_fruit::BonusFruitPlugin);
+            }
+            GameMode::FoxMaze => {
+                group
+                    .add(lives::LivesPlugin(self.0))
+                    .add(maze::MazePlugin)
+                    .add(ghost::GhostPlugin(self.0));
+            }
+        }
+    }
+}
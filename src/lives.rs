@@ -0,0 +1,250 @@
+//! Lives, the death sequence and the game-over screen.
+//!
+//! Touching an enemy that is neither frightened nor respawning, or swapping
+//! cells with one head-on, sends `PlayerDied`. The player then spins for
+//! `DEATH_SECS` in `AppState::Dying`, after which every actor goes back to its
+//! starting point, or the game is over when no lives are left. The fox only
+//! has lives in the maze.
+
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::collision::{
+    check_for_collisions, check_for_collisions_with_fox, snake_eating, GroundBox,
+};
+use crate::components::{Enemy, Player, Size};
+use crate::ghost::{ghost_movement, GhostSchedule};
+use crate::grid::{meet, Direction, Position, PreviousPosition};
+use crate::level::Level;
+use crate::maze;
+use crate::movement::Heading;
+use crate::power::{Frightened, Home, PowerState, Respawning};
+use crate::speed::StepProgress;
+use crate::state::AppState;
+use crate::GameMode;
+
+pub const STARTING_LIVES: u32 = 3;
+/// Length of the death sequence in seconds.
+const DEATH_SECS: f32 = 1.5;
+
+pub struct LivesPlugin(pub GameMode);
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Lives(STARTING_LIVES))
+            .add_event::<PlayerDied>()
+            .add_system_set(SystemSet::on_enter(AppState::Dying).with_system(lose_life))
+            .add_system_set(SystemSet::on_update(AppState::Dying).with_system(play_death));
+        match self.0 {
+            GameMode::Pacman => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(check_for_enemy_contact.after(check_for_collisions))
+                        .with_system(start_dying.after(check_for_enemy_contact)),
+                )
+                .add_system_set(
+                    SystemSet::on_exit(AppState::Dying).with_system(reset_pacman_round),
+                );
+            }
+            GameMode::Packman2 => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        // judged once both the snake and the ghosts have moved
+                        .with_system(
+                            check_for_ghost_contact
+                                .after(snake_eating)
+                                .after(ghost_movement),
+                        )
+                        .with_system(start_dying.after(check_for_ghost_contact)),
+                )
+                .add_system_set(SystemSet::on_exit(AppState::Dying).with_system(reset_grid_round));
+            }
+            GameMode::FoxMaze => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(check_for_fox_caught.after(check_for_collisions_with_fox))
+                        .with_system(start_dying.after(check_for_fox_caught)),
+                )
+                .add_system_set(SystemSet::on_exit(AppState::Dying).with_system(reset_maze_round));
+            }
+            GameMode::Fox => {}
+        }
+    }
+}
+
+/// Lives left, including the one being played.
+pub struct Lives(pub u32);
+
+/// Sent when an enemy catches the player.
+#[derive(Default)]
+pub struct PlayerDied;
+
+struct DeathTimer(Timer);
+
+pub fn check_for_enemy_contact(
+    pacman_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Frightened>, Without<Respawning>)>,
+    mut died_events: EventWriter<PlayerDied>,
+) {
+    let pacman_transform = pacman_query.single();
+    for enemy_transform in &enemy_query {
+        let collision = collide(
+            pacman_transform.translation,
+            Vec2::splat(50.),
+            enemy_transform.translation,
+            Vec2::splat(50.),
+        );
+
+        if collision.is_some() {
+            died_events.send_default();
+            return;
+        }
+    }
+}
+
+pub fn check_for_ghost_contact(
+    heads: Query<(&Position, &PreviousPosition), With<Player>>,
+    ghosts: Query<
+        (&Position, &PreviousPosition),
+        (With<Enemy>, Without<Frightened>, Without<Respawning>),
+    >,
+    mut died_events: EventWriter<PlayerDied>,
+) {
+    for (head_pos, head_from) in heads.iter() {
+        if ghosts
+            .iter()
+            .any(|(ghost_pos, ghost_from)| meet(*head_pos, head_from.0, *ghost_pos, ghost_from.0))
+        {
+            died_events.send_default();
+            return;
+        }
+    }
+}
+
+pub fn check_for_fox_caught(
+    fox_query: Query<(&Transform, &Size), With<Player>>,
+    ghost_query: Query<(&Transform, &Size), (With<Enemy>, Without<Player>)>,
+    mut died_events: EventWriter<PlayerDied>,
+) {
+    let (fox_transform, fox_size) = match fox_query.get_single() {
+        Ok(fox) => fox,
+        Err(_) => return,
+    };
+    let fox_box = GroundBox::of(fox_transform, fox_size);
+    if ghost_query
+        .iter()
+        .any(|(transform, size)| fox_box.overlaps(&GroundBox::of(transform, size)))
+    {
+        died_events.send_default();
+    }
+}
+
+fn start_dying(mut died_events: EventReader<PlayerDied>, mut state: ResMut<State<AppState>>) {
+    if died_events.iter().count() > 0 {
+        let _ = state.push(AppState::Dying);
+    }
+}
+
+fn lose_life(mut commands: Commands, mut lives: ResMut<Lives>) {
+    lives.0 = lives.0.saturating_sub(1);
+    commands.insert_resource(DeathTimer(Timer::from_seconds(DEATH_SECS, false)));
+}
+
+fn play_death(
+    time: Res<Time>,
+    lives: Res<Lives>,
+    mut timer: ResMut<DeathTimer>,
+    mut state: ResMut<State<AppState>>,
+    mut player: Query<&mut Transform, With<Player>>,
+) {
+    for mut transform in player.iter_mut() {
+        transform.rotate_z(TAU * 2. * time.delta_seconds());
+    }
+
+    if timer.0.tick(time.delta()).just_finished() {
+        let _ = if lives.0 == 0 {
+            state.replace(AppState::GameOver)
+        } else {
+            state.pop()
+        };
+    }
+}
+
+fn reset_pacman_round(
+    mut commands: Commands,
+    mut power: ResMut<PowerState>,
+    mut schedule: ResMut<GhostSchedule>,
+    mut player: Query<(&mut Transform, &mut Heading), (With<Player>, Without<Enemy>)>,
+    mut enemies: Query<(Entity, &mut Transform, &Home), With<Enemy>>,
+) {
+    *power = PowerState::default();
+    *schedule = GhostSchedule::default();
+    for (mut transform, mut heading) in player.iter_mut() {
+        *heading = Heading::default();
+        *transform = Transform {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            ..*transform
+        };
+    }
+    for (entity, mut transform, home) in enemies.iter_mut() {
+        transform.translation = home.0;
+        commands
+            .entity(entity)
+            .remove::<Frightened>()
+            .remove::<Respawning>();
+    }
+}
+
+fn reset_grid_round(
+    mut commands: Commands,
+    level: Res<Level>,
+    mut power: ResMut<PowerState>,
+    mut schedule: ResMut<GhostSchedule>,
+    mut player: Query<
+        (&mut Position, &mut Direction, &mut Transform),
+        (With<Player>, Without<Enemy>),
+    >,
+    mut ghosts: Query<(Entity, &mut Position, &mut Direction), With<Enemy>>,
+) {
+    *power = PowerState::default();
+    *schedule = GhostSchedule::default();
+    for (mut pos, mut direction, mut transform) in player.iter_mut() {
+        *pos = level.player_start().unwrap();
+        *direction = Direction::Stay;
+        transform.rotation = Quat::IDENTITY;
+    }
+    let pens = level.ghost_starts();
+    for ((entity, mut pos, mut direction), pen) in ghosts.iter_mut().zip(pens.iter().cycle()) {
+        *pos = *pen;
+        *direction = Direction::Up;
+        commands
+            .entity(entity)
+            .remove::<Frightened>()
+            .remove::<Respawning>();
+    }
+}
+
+fn reset_maze_round(
+    level: Res<Level>,
+    mut schedule: ResMut<GhostSchedule>,
+    mut fox: Query<(&mut Transform, &mut Position, &mut Direction), (With<Player>, Without<Enemy>)>,
+    mut ghosts: Query<(&mut Position, &mut Direction, &mut StepProgress), With<Enemy>>,
+) {
+    *schedule = GhostSchedule::default();
+    let start = level.player_start().unwrap();
+    for (mut transform, mut pos, mut direction) in fox.iter_mut() {
+        transform.translation = maze::cell_center(&level, start);
+        transform.rotation = Quat::IDENTITY;
+        *pos = start;
+        *direction = Direction::Stay;
+    }
+    let pens = level.ghost_starts();
+    for ((mut pos, mut direction, mut progress), pen) in ghosts.iter_mut().zip(pens.iter().cycle())
+    {
+        *pos = *pen;
+        *direction = Direction::Up;
+        *progress = StepProgress::default();
+    }
+}
@@ -0,0 +1,228 @@
+//! The fox's 3D maze (`fox --maze`).
+//!
+//! The levels of `assets/levels` are built out of boxes: every wall cell is a
+//! solid `Obstacle` the fox slides along, and pellets lie along the corridors
+//! for it to pick up. The ghosts of `ghost` chase the fox through the same
+//! `Level` grid as in packman2, so the fox keeps a `Position` and `Direction`
+//! of the cell it stands on.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::collision::keep_fox_in_arena;
+use crate::components::{Collider, Enemy, InGame, Obstacle, Pellet, Player, Points, Size};
+use crate::ghost::ghost_movement;
+use crate::grid::{Direction, Position, WALL_COLOR};
+use crate::level::{CurrentLevel, Level, Tile, Wall, PELLET_COLOR};
+use crate::spawning::spawn_fox_at;
+use crate::speed::{tune_speeds, LevelSpeeds, StepProgress};
+use crate::state::AppState;
+
+/// Width and depth of one cell of the maze.
+pub const MAZE_CELL: f32 = 50.;
+const WALL_HEIGHT: f32 = 60.;
+/// The fox is shrunk to turn around in a corridor.
+const FOX_SCALE: f32 = 0.3;
+/// Height pellets float at.
+const PELLET_HEIGHT: f32 = 15.;
+
+pub struct MazePlugin;
+
+impl Plugin for MazePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Playing).with_system(load_maze.after(tune_speeds)),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Playing).with_system(
+                track_fox_cell
+                    .after(keep_fox_in_arena)
+                    .before(ghost_movement),
+            ),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            place_ghosts.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Centre of the floor of `pos`. The top row of the map is the far end of
+/// the maze, at negative z.
+pub fn cell_center(level: &Level, pos: Position) -> Vec3 {
+    Vec3::new(
+        (pos.x as f32 + 0.5 - level.width() as f32 / 2.) * MAZE_CELL,
+        0.,
+        (level.height() as f32 / 2. - pos.y as f32 - 0.5) * MAZE_CELL,
+    )
+}
+
+/// The cell `translation` is in.
+pub fn cell_at(level: &Level, translation: Vec3) -> Position {
+    Position {
+        x: (translation.x / MAZE_CELL + level.width() as f32 / 2.).floor() as i32,
+        y: (level.height() as f32 / 2. - translation.z / MAZE_CELL).floor() as i32,
+    }
+}
+
+/// Spawns the walls, pellets and fox of the current level.
+fn load_maze(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    current_level: Res<CurrentLevel>,
+    speeds: Res<LevelSpeeds>,
+) {
+    let level = Level::load_for_play(current_level.0);
+
+    let wall_mesh = meshes.add(Mesh::from(shape::Box::new(
+        MAZE_CELL,
+        WALL_HEIGHT,
+        MAZE_CELL,
+    )));
+    let wall_material = materials.add(WALL_COLOR.into());
+    let pellet_material = materials.add(PELLET_COLOR.into());
+
+    for (pos, tile) in level.cells() {
+        let center = cell_center(&level, pos);
+        match tile {
+            Tile::Wall => {
+                commands
+                    .spawn_bundle(PbrBundle {
+                        mesh: wall_mesh.clone(),
+                        material: wall_material.clone(),
+                        transform: Transform::from_translation(center + Vec3::Y * WALL_HEIGHT / 2.),
+                        ..default()
+                    })
+                    .insert(Wall)
+                    .insert(Obstacle)
+                    .insert(InGame)
+                    .insert(Size(Vec3::new(MAZE_CELL, WALL_HEIGHT, MAZE_CELL)));
+            }
+            // there is nothing to power up in the maze; power pellets are
+            // just worth more
+            Tile::Pellet | Tile::PowerPellet => {
+                let (radius, points) = if tile == Tile::Pellet {
+                    (MAZE_CELL * 0.1, 10)
+                } else {
+                    (MAZE_CELL * 0.25, 50)
+                };
+                commands
+                    .spawn_bundle(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Icosphere {
+                            radius,
+                            subdivisions: 2,
+                        })),
+                        material: pellet_material.clone(),
+                        transform: Transform::from_translation(center + Vec3::Y * PELLET_HEIGHT),
+                        ..default()
+                    })
+                    .insert(Pellet)
+                    .insert(Collider)
+                    .insert(InGame)
+                    .insert(Points(points))
+                    .insert(Size(Vec3::splat(radius * 2.)));
+            }
+            Tile::PlayerStart => {
+                spawn_fox_at(
+                    &mut commands,
+                    &asset_server,
+                    speeds.0.fox_walk,
+                    Transform::from_translation(center).with_scale(Vec3::splat(FOX_SCALE)),
+                )
+                .insert(pos)
+                .insert(Direction::Stay);
+            }
+            Tile::Empty | Tile::GhostStart | Tile::Tunnel => {}
+        }
+    }
+
+    commands.insert_resource(level);
+}
+
+/// Keeps the fox's cell and heading up to date for the ghosts.
+fn track_fox_cell(
+    level: Res<Level>,
+    mut fox_query: Query<(&Transform, &mut Position, &mut Direction), With<Player>>,
+) {
+    for (transform, mut pos, mut direction) in fox_query.iter_mut() {
+        *pos = cell_at(&level, transform.translation);
+        // the fox runs towards its back
+        let facing = transform.back();
+        *direction = if facing.x.abs() > facing.z.abs() {
+            if facing.x < 0. {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        } else if facing.z < 0. {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+    }
+}
+
+/// Moves every ghost between the cell it came from and the one it is in. A
+/// ghost that came through a tunnel is put straight on its cell.
+fn place_ghosts(
+    level: Option<Res<Level>>,
+    mut ghosts: Query<(&Position, &Direction, &StepProgress, &Size, &mut Transform), With<Enemy>>,
+) {
+    let level = match level {
+        Some(level) => level,
+        None => return,
+    };
+    for (pos, direction, progress, size, mut transform) in ghosts.iter_mut() {
+        let from = level.step(*pos, direction.opposite());
+        let to = cell_center(&level, *pos);
+        let floor = if from.is_next_to(*pos) {
+            cell_center(&level, from).lerp(to, progress.0)
+        } else {
+            to
+        };
+        transform.translation = floor + Vec3::Y * size.0.y / 2.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ghost_floor(pos: Position, direction: Direction) -> Vec3 {
+        let level = Level::parse("edges: tunnels\n#####\nT.P.T\n#####\n").unwrap();
+        let mut app = App::new();
+        app.insert_resource(level).add_system(place_ghosts);
+        let ghost = app
+            .world
+            .spawn()
+            .insert(Enemy)
+            .insert(pos)
+            .insert(direction)
+            .insert(StepProgress(0.5))
+            .insert(Size(Vec3::ZERO))
+            .insert(Transform::default())
+            .id();
+
+        app.update();
+
+        app.world.get::<Transform>(ghost).unwrap().translation
+    }
+
+    #[test]
+    fn ghosts_are_drawn_between_cells() {
+        // from the player start to the cell on its right
+        assert_eq!(
+            ghost_floor(Position { x: 3, y: 1 }, Direction::Right),
+            Vec3::new(MAZE_CELL / 2., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn ghosts_coming_out_of_a_tunnel_are_not_drawn_across_the_maze() {
+        assert_eq!(
+            ghost_floor(Position { x: 0, y: 1 }, Direction::Right),
+            Vec3::new(-2. * MAZE_CELL, 0., 0.)
+        );
+    }
+}
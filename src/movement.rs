@@ -0,0 +1,400 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{Action, ActionState};
+use crate::components::Player;
+use crate::grid::{Direction, Position};
+use crate::level::Level;
+use crate::play_area::PlayArea;
+use crate::speed::{Speed, SpeedSettings, StepProgress};
+use crate::state::AppState;
+use crate::GameMode;
+
+pub struct MovementPlugin(pub GameMode);
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        match self.0 {
+            GameMode::Pacman => {
+                app.init_resource::<PacmanMovement>().add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(move_pacman)
+                        .with_system(warp_pacman.after(move_pacman)),
+                );
+            }
+            GameMode::Fox | GameMode::FoxMaze => {
+                app.add_event::<FoxMoveEvent>()
+                    .add_event::<FoxRunEvent>()
+                    .add_system_set(
+                        SystemSet::on_update(AppState::Playing)
+                            .with_system(run_fox)
+                            .with_system(move_fox.after(run_fox)),
+                    );
+            }
+            GameMode::Packman2 => {
+                app.add_event::<HeadStepped>().add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(snake_movement_input.before(snake_movement))
+                        .with_system(snake_movement),
+                );
+            }
+        }
+    }
+}
+
+/// Spacing of the centrelines pacman follows in `PacmanMovement::GridLocked`.
+pub const GRID_CELL: f32 = 50.;
+
+/// How pacman is steered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum PacmanMovement {
+    /// Moves only while a direction is held, diagonals included.
+    #[default]
+    Free,
+    /// Follows the centrelines of a `GRID_CELL` grid and keeps going without
+    /// input; a turn is remembered until the next cell centre.
+    GridLocked,
+}
+
+/// Where pacman is going in `PacmanMovement::GridLocked`.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Heading {
+    pub current: Direction,
+    /// Turn to take at the next cell centre.
+    pub queued: Option<Direction>,
+}
+
+impl Default for Heading {
+    fn default() -> Self {
+        Self {
+            current: Direction::Stay,
+            queued: None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FoxMoveEvent;
+
+#[derive(Default)]
+pub struct FoxRunEvent;
+
+/// Sent every time the snake's head moves on to the next cell.
+pub struct HeadStepped {
+    /// Cell the head left.
+    pub from: Position,
+}
+
+pub fn move_pacman(
+    time: Res<Time>,
+    movement: Res<PacmanMovement>,
+    actions: Res<ActionState>,
+    mut query: Query<(&mut Transform, &mut Heading, &Speed), With<Player>>,
+) {
+    let (mut pacman_transform, mut heading, speed) = query.single_mut();
+    let step = speed.0 * time.delta_seconds();
+    match *movement {
+        PacmanMovement::Free => move_freely(&mut pacman_transform, &actions, step),
+        PacmanMovement::GridLocked => move_on_grid(
+            &mut pacman_transform.translation,
+            &mut heading,
+            &actions,
+            step,
+        ),
+    }
+}
+
+fn move_freely(pacman_transform: &mut Transform, actions: &ActionState, step: f32) {
+    if actions.pressed(Action::MoveLeft) {
+        pacman_transform.translation.x -= step;
+    }
+    if actions.pressed(Action::MoveRight) {
+        pacman_transform.translation.x += step;
+    }
+    if actions.pressed(Action::MoveUp) {
+        pacman_transform.translation.y += step;
+    }
+    if actions.pressed(Action::MoveDown) {
+        pacman_transform.translation.y -= step;
+    }
+}
+
+/// Direction held on the controls, if any.
+fn held_direction(actions: &ActionState) -> Option<Direction> {
+    [
+        (Action::MoveLeft, Direction::Left),
+        (Action::MoveRight, Direction::Right),
+        (Action::MoveUp, Direction::Up),
+        (Action::MoveDown, Direction::Down),
+    ]
+    .into_iter()
+    .find(|(action, _)| actions.pressed(*action))
+    .map(|(_, direction)| direction)
+}
+
+fn unit(direction: Direction) -> Vec3 {
+    match direction {
+        Direction::Left => -Vec3::X,
+        Direction::Right => Vec3::X,
+        Direction::Up => Vec3::Y,
+        Direction::Down => -Vec3::Y,
+        Direction::Stay => Vec3::ZERO,
+    }
+}
+
+fn snap(value: f32) -> f32 {
+    (value / GRID_CELL).round() * GRID_CELL
+}
+
+/// Moves the centre of pacman onto the centreline it is heading along.
+fn snap_to_centreline(translation: &mut Vec3, direction: Direction) {
+    match direction {
+        Direction::Left | Direction::Right => translation.y = snap(translation.y),
+        Direction::Up | Direction::Down => translation.x = snap(translation.x),
+        Direction::Stay => {}
+    }
+}
+
+/// Distance left to the next cell centre ahead of `translation`.
+fn distance_to_centre(translation: Vec3, direction: Direction) -> f32 {
+    let ahead = translation.dot(unit(direction));
+    (ahead / GRID_CELL).ceil() * GRID_CELL - ahead
+}
+
+fn move_on_grid(translation: &mut Vec3, heading: &mut Heading, actions: &ActionState, step: f32) {
+    if let Some(direction) = held_direction(actions) {
+        heading.queued = Some(direction);
+    }
+    // starting and reversing never have to wait for a cell centre
+    if let Some(queued) = heading.queued {
+        if heading.current == Direction::Stay
+            || queued == heading.current
+            || queued == heading.current.opposite()
+        {
+            heading.current = queued;
+            heading.queued = None;
+        }
+    }
+    if heading.current == Direction::Stay {
+        return;
+    }
+
+    let mut step = step;
+    snap_to_centreline(translation, heading.current);
+    if let Some(queued) = heading.queued {
+        let to_centre = distance_to_centre(*translation, heading.current);
+        if to_centre <= step {
+            *translation += unit(heading.current) * to_centre;
+            step -= to_centre;
+            heading.current = queued;
+            heading.queued = None;
+            snap_to_centreline(translation, heading.current);
+        }
+    }
+    *translation += unit(heading.current) * step;
+}
+
+pub fn warp_pacman(area: Res<PlayArea>, mut pacman_query: Query<&mut Transform, With<Player>>) {
+    let mut pacman_transform = pacman_query.single_mut();
+
+    if pacman_transform.translation.x.abs() > area.width / 2. {
+        if pacman_transform.translation.x > 0. {
+            pacman_transform.translation.x -= area.width;
+        } else {
+            pacman_transform.translation.x += area.width;
+        }
+    }
+    if pacman_transform.translation.y.abs() > area.height / 2. {
+        if pacman_transform.translation.y > 0. {
+            pacman_transform.translation.y -= area.height;
+        } else {
+            pacman_transform.translation.y += area.height;
+        }
+    }
+}
+
+pub fn run_fox(actions: Res<ActionState>, mut fox_run_events: EventWriter<FoxRunEvent>) {
+    if actions.pressed(Action::Run) {
+        fox_run_events.send_default();
+    }
+}
+
+pub fn move_fox(
+    time: Res<Time>,
+    speeds: Res<SpeedSettings>,
+    mut query: Query<(&mut Transform, &Speed), With<Player>>,
+    actions: Res<ActionState>,
+    mut fox_move_events: EventWriter<FoxMoveEvent>,
+    fox_run_events: EventReader<FoxRunEvent>,
+) {
+    let (mut fox_transform, speed) = query.single_mut();
+    let mut tmp = fox_transform.translation;
+    // the fox's `Speed` is its walking speed; running scales it like the settings do
+    let run_factor = if fox_run_events.is_empty() {
+        1.
+    } else {
+        speeds.fox_run / speeds.fox_walk
+    };
+    let fox_speed = speed.0 * run_factor * time.delta_seconds();
+
+    if actions.pressed(Action::MoveLeft) {
+        fox_transform.translation.x -= fox_speed;
+        tmp.x += 100.;
+    }
+    if actions.pressed(Action::MoveRight) {
+        fox_transform.translation.x += fox_speed;
+        tmp.x -= 100.;
+    }
+    if actions.pressed(Action::MoveDown) {
+        fox_transform.translation.z += fox_speed;
+        tmp.z -= 100.;
+    }
+    if actions.pressed(Action::MoveUp) {
+        fox_transform.translation.z -= fox_speed;
+        tmp.z += 100.;
+    }
+    if actions.any_pressed([
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::MoveUp,
+    ]) && tmp != fox_transform.translation
+    {
+        fox_transform.look_at(tmp, Vec3::Y);
+        fox_move_events.send_default();
+    }
+}
+
+/// Turns the snake's head to the direction held. Letting go keeps it going,
+/// so that it never forgets which way it is facing and turns back into its
+/// tail.
+pub fn snake_movement_input(
+    actions: Res<ActionState>,
+    mut heads: Query<&mut Direction, With<Player>>,
+) {
+    if let Some(mut direction) = heads.iter_mut().next() {
+        let dir: Direction = if actions.pressed(Action::MoveLeft) {
+            Direction::Left
+        } else if actions.pressed(Action::MoveDown) {
+            Direction::Down
+        } else if actions.pressed(Action::MoveUp) {
+            Direction::Up
+        } else if actions.pressed(Action::MoveRight) {
+            Direction::Right
+        } else {
+            return;
+        };
+        if dir != direction.opposite() {
+            *direction = dir;
+        }
+    }
+}
+
+pub fn snake_movement(
+    time: Res<Time>,
+    level: Res<Level>,
+    mut heads: Query<(&mut Position, &Direction, &Speed, &mut StepProgress), With<Player>>,
+    mut step_events: EventWriter<HeadStepped>,
+) {
+    if let Some((mut head_pos, direction, speed, mut progress)) = heads.iter_mut().next() {
+        if *direction == Direction::Stay {
+            progress.0 = 0.;
+            return;
+        }
+        for _ in 0..progress.advance(speed.0 * time.delta_seconds()) {
+            let next = level.step(*head_pos, *direction);
+            if !level.is_wall(next) {
+                step_events.send(HeadStepped { from: *head_pos });
+                *head_pos = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(action: Action) -> ActionState {
+        let mut actions = ActionState::default();
+        actions.set_pressed([action].into_iter().collect());
+        actions
+    }
+
+    fn going(current: Direction) -> Heading {
+        Heading {
+            current,
+            queued: None,
+        }
+    }
+
+    #[test]
+    fn a_turn_waits_for_the_next_cell_centre() {
+        let mut translation = Vec3::new(10., 0., 0.);
+        let mut heading = going(Direction::Right);
+
+        move_on_grid(
+            &mut translation,
+            &mut heading,
+            &holding(Action::MoveUp),
+            10.,
+        );
+        assert_eq!(translation, Vec3::new(20., 0., 0.));
+        assert_eq!(heading.queued, Some(Direction::Up));
+
+        // still remembered once the key is let go
+        move_on_grid(&mut translation, &mut heading, &ActionState::default(), 10.);
+        assert_eq!(translation, Vec3::new(30., 0., 0.));
+        assert_eq!(heading.current, Direction::Right);
+        assert_eq!(heading.queued, Some(Direction::Up));
+    }
+
+    #[test]
+    fn a_turn_is_taken_at_the_cell_centre_onto_the_new_centreline() {
+        let mut translation = Vec3::new(45., 3., 0.);
+        let mut heading = Heading {
+            current: Direction::Right,
+            queued: Some(Direction::Up),
+        };
+
+        move_on_grid(&mut translation, &mut heading, &ActionState::default(), 10.);
+
+        // 5 to the centre at (50, 0), and the rest of the step up from there
+        assert_eq!(translation, Vec3::new(50., 5., 0.));
+        assert_eq!(heading, going(Direction::Up));
+    }
+
+    #[test]
+    fn keeps_going_without_input() {
+        let mut translation = Vec3::ZERO;
+        let mut heading = going(Direction::Left);
+
+        move_on_grid(&mut translation, &mut heading, &ActionState::default(), 10.);
+        move_on_grid(&mut translation, &mut heading, &ActionState::default(), 10.);
+
+        assert_eq!(translation, Vec3::new(-20., 0., 0.));
+        assert_eq!(heading, going(Direction::Left));
+    }
+
+    #[test]
+    fn the_snake_cannot_turn_back_after_letting_go() {
+        let mut app = App::new();
+        app.insert_resource(holding(Action::MoveRight))
+            .add_system(snake_movement_input);
+        let head = app
+            .world
+            .spawn()
+            .insert(Player)
+            .insert(Direction::Stay)
+            .id();
+
+        app.update();
+        app.insert_resource(ActionState::default());
+        app.update();
+        assert_eq!(app.world.get::<Direction>(head), Some(&Direction::Right));
+
+        app.insert_resource(holding(Action::MoveLeft));
+        app.update();
+        assert_eq!(app.world.get::<Direction>(head), Some(&Direction::Right));
+    }
+}
@@ -1,309 +1,32 @@
-use bevy::render::texture;
-use bevy::time::FixedTimestep;
 use bevy::prelude::*;
-use rand::prelude::random;
-
-const SNAKE_HEAD_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
-const FOOD_COLOR: Color = Color::rgb(1.0, 0.0, 1.0);
-const WALL_COLOR: Color = Color::rgb(0.5,0.0,1.0);
-
-const UNIT_WIDTH: u32  = 20;
-const UNIT_HEIGHT: u32 = 20;
-
-const X_LENGTH: u32 = 32;
-const Y_LENGTH: u32 = 25;
-
-const SCREEN_WIDTH: u32 = UNIT_WIDTH * X_LENGTH;
-const SCREEN_HEIGHT: u32 = UNIT_HEIGHT * Y_LENGTH;
-
-const WALL_THICKNESS: f32 = 10.0;
-// x coordinates
-const LEFT_WALL: f32 = -450.;
-const RIGHT_WALL: f32 = 450.;
-// y coordinates
-const BOTTOM_WALL: f32 = -300.;
-const TOP_WALL: f32 = 300.;
-
-#[derive(Component, Clone, Copy, PartialEq, Eq)]
-struct Position {
-    x: i32,
-    y: i32,
-}
-
-#[derive(Component)]
-struct Size {
-    width: f32,
-    height: f32,
-}
-impl Size {
-    pub fn square(x: f32) -> Self {
-        Self {
-            width: x,
-            height: x,
-        }
-    }
-}
-
-#[derive(Component)]
-struct SnakeHead {
-    direction: Direction,
-}
-
-struct GrowthEvent;
-
-#[derive(Component)]
-struct Food;
-
-#[derive(Component)]
-struct Enemy{
-    direction: Direction,
-}
-
-#[derive(PartialEq, Copy, Clone)]
-enum Direction {
-    Left,
-    Up,
-    Right,
-    Down,
-    Stay
-}
-
-impl Direction {
-    fn opposite(self) -> Self {
-        match self {
-            Self::Left => Self::Right,
-            Self::Right => Self::Left,
-            Self::Up => Self::Down,
-            Self::Down => Self::Up,
-            Self::Stay => Self::Stay
-        }
-    }
-}
+use pac_human::cli;
+use pac_human::grid::GridConfig;
+use pac_human::snake::TailMode;
+use pac_human::{GameMode, PacHumanPlugins};
 
 fn setup_camera(mut commands: Commands) {
     commands.spawn_bundle(Camera2dBundle::default());
 }
 
-fn spawn_snake(mut commands: Commands,asset_server: Res<AssetServer>) {
-    commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: SNAKE_HEAD_COLOR,
-                ..default()
-            },
-            transform: Transform {
-                scale: Vec3::new(10.0, 10.0, 10.0),
-                ..default()
-            },
-            ..default()
-        })
-        .insert_bundle(SpriteBundle {
-            texture: asset_server.load("../assets/images/pacman.png"),
-        ..default()},
-        )
-        .insert(SnakeHead {
-            direction: Direction::Up,
-        })
-        .insert(Position { x: 3, y: 3 })
-       // .insert(texture : asset_server.load("../assets/images/pacman.png"))
-        .insert(Size::square(0.002));
-}
-
-fn snake_movement_input(keyboard_input: Res<Input<KeyCode>>, mut heads: Query<&mut SnakeHead>) {
-    if let Some(mut head) = heads.iter_mut().next() {
-        let dir: Direction = if keyboard_input.pressed(KeyCode::H) {
-            Direction::Left
-        } else if keyboard_input.pressed(KeyCode::J) {
-            Direction::Down
-        } else if keyboard_input.pressed(KeyCode::K) {
-            Direction::Up
-        } else if keyboard_input.pressed(KeyCode::L) {
-            Direction::Right
-        } else {
-            Direction::Stay
-        };
-        if dir != head.direction.opposite() {
-            head.direction = dir;
-        }
-    }
-}
-
-fn snake_movement(mut heads: Query<(&mut Position, &SnakeHead)>) {
-    if let Some((mut head_pos, head)) = heads.iter_mut().next() {
-        if head_pos.x < 0
-            || head_pos.y < 0
-            || head_pos.x as u32 >= SCREEN_WIDTH
-            || head_pos.y as u32 >= SCREEN_HEIGHT
-        {
-            //head.direction == {Direction::Stay;}
-        }
-        match &head.direction {
-            Direction::Left => {
-                head_pos.x -= 1;
-            }
-            Direction::Right => {
-                head_pos.x += 1;
-            }
-            Direction::Up => {
-                head_pos.y += 1;
-            }
-            Direction::Down => {
-                head_pos.y -= 1;
-            }
-            Direction::Stay => {}
-        };
-    }
-}
-fn enemy_movement(mut enemys: Query<(&mut Position, &Enemy)>) {
-    for (mut enemy_pos, enemy) in enemys.iter_mut() {
-        if enemy_pos.x < 0
-            || enemy_pos.y < 0
-            || enemy_pos.x as u32 >= SCREEN_WIDTH
-            || enemy_pos.y as u32 >= SCREEN_HEIGHT
-        {
-            //head.direction == {Direction::Stay;}
-        }
-        match &enemy.direction {
-            Direction::Left => {
-                enemy_pos.x -= 1;
-            }
-            Direction::Right => {
-                enemy_pos.x += 1;
-            }
-            Direction::Up => {
-                enemy_pos.y += 1;
-            }
-            Direction::Down => {
-                enemy_pos.y -= 1;
-            }
-            Direction::Stay => {}
-        };
-    }
-}
-
-fn size_scaling(windows: Res<Windows>, mut q: Query<(&Size, &mut Transform)>) {
-    let window = windows.get_primary().unwrap();
-    for (sprite_size, mut transform) in q.iter_mut() {
-        transform.scale = Vec3::new(
-            sprite_size.width / X_LENGTH as f32 * window.width() as f32,
-            sprite_size.height / Y_LENGTH as f32 * window.height() as f32,
-            1.0,
-        );
-    }
-}
-
-fn position_translation(windows: Res<Windows>, mut q: Query<(&Position, &mut Transform)>) {
-    fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
-        let tile_size = bound_window / bound_game;
-        pos / bound_game * bound_window - (bound_window / 2.) + (tile_size / 2.)
-    }
-    let window = windows.get_primary().unwrap();
-    for (pos, mut transform) in q.iter_mut() {
-        transform.translation = Vec3::new(
-            convert(pos.x as f32, window.width() as f32, UNIT_WIDTH as f32),
-            convert(pos.y as f32, window.height() as f32, UNIT_HEIGHT as f32),
-            0.0,
-        );
-    }
-}
-
-fn snake_eating(
-    mut commands: Commands,
-    //mut growth_writer: EventWriter<GrowthEvent>,
-    food_positions: Query<(Entity, &Position), With<Food>>,
-    head_positions: Query<&Position, With<SnakeHead>>,
-) {
-    for head_pos in head_positions.iter() {
-        for (ent, food_pos) in food_positions.iter() {
-            if food_pos == head_pos {
-                commands.entity(ent).despawn();
-                //growth_writer.send(GrowthEvent);
-            }
-        }
-    }
-}
-
-
-fn food_spawner(mut commands: Commands,asset_server: Res<AssetServer>) {
-    let entry = commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                ..default()
-            },
-            ..default()
-        })
-        .insert_bundle(SpriteBundle {
-            texture: asset_server.load("../assets/images/fruit.png"),
-        ..default()},
-        )
-        .insert(Food)
-        .insert(Position {
-            x: (random::<f32>() * UNIT_WIDTH as f32) as i32,
-            y: (random::<f32>() * UNIT_HEIGHT as f32) as i32,
-        })
-        .insert(Size::square(0.004)).id();
-        println!("Food {}",entry.id())
-}
-
-fn enemy_spawner(mut commands: Commands,asset_server: Res<AssetServer>) {
-    let entry = commands
-        .spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                ..default()
-            },
-            ..default()
-        })
-        .insert_bundle(SpriteBundle {
-            texture: asset_server.load("../assets/images/enemy.png"),
-        ..default()},
-        )
-        .insert(Enemy{
-            direction: Direction::Up
-        })
-        .insert(Position {
-            x: (random::<f32>() * UNIT_WIDTH as f32) as i32,
-            y: (random::<f32>() * UNIT_HEIGHT as f32) as i32,
-        })
-        .insert(Size::square(0.002));
-}
-
-
 fn main() {
+    let (tail, title) = if cli::has_flag("snake") {
+        (TailMode::Growing, "Snake!")
+    } else {
+        (TailMode::None, "Packman2")
+    };
+    let grid = GridConfig::default();
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
+        .insert_resource(tail)
+        .insert_resource(grid)
         .insert_resource(WindowDescriptor {
-            title: "Snake!".to_string(),
-            width: SCREEN_WIDTH as f32,
-            height: SCREEN_HEIGHT as f32,
+            title: title.to_string(),
+            width: grid.width(),
+            height: grid.height(),
             ..default()
         })
         .add_startup_system(setup_camera)
-        .add_startup_system(spawn_snake)
-        //.add_startup_system()
-        .add_system(snake_movement_input.before(snake_movement))
-        .add_system_set(
-            SystemSet::new()
-                .with_run_criteria(FixedTimestep::step(0.150))
-                .with_system(snake_movement)
-                .with_system(snake_eating.after(snake_movement)),
-        )
-        .add_system_set(
-            SystemSet::new()
-                .with_run_criteria(FixedTimestep::step(3.0))
-                .with_system(food_spawner),
-        )
-        .add_system_set(
-            SystemSet::new()
-            .with_run_criteria(FixedTimestep::step(3.0))
-            .with_system(enemy_spawner)
-            .with_system(enemy_movement),
-        )
-        .add_system_set_to_stage(
-            CoreStage::PostUpdate,
-            SystemSet::new()
-                .with_system(position_translation)
-                .with_system(size_scaling),
-        )
         .add_plugins(DefaultPlugins)
+        .add_plugins(PacHumanPlugins(GameMode::Packman2))
         .run();
-}
\ No newline at end of file
+}
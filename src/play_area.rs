@@ -0,0 +1,46 @@
+//! Size of the area the game is drawn in.
+//!
+//! `PlayArea` follows the primary window when there is one, so that gameplay
+//! systems never have to look at `Windows` and also run without a window.
+
+use bevy::prelude::*;
+
+pub struct PlayAreaPlugin;
+
+impl Plugin for PlayAreaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayArea>()
+            .add_system_to_stage(CoreStage::PreUpdate, follow_window);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlayArea {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for PlayArea {
+    /// Same as the default window.
+    fn default() -> Self {
+        let window = WindowDescriptor::default();
+        Self {
+            width: window.width,
+            height: window.height,
+        }
+    }
+}
+
+pub fn follow_window(windows: Option<Res<Windows>>, mut area: ResMut<PlayArea>) {
+    let window = match windows.as_ref().and_then(|windows| windows.get_primary()) {
+        Some(window) => window,
+        None => return,
+    };
+    let size = PlayArea {
+        width: window.width(),
+        height: window.height(),
+    };
+    if *area != size {
+        *area = size;
+    }
+}
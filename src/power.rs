@@ -0,0 +1,276 @@
+//! Power pellets and frightened enemies.
+//!
+//! Eating a `PowerPellet` frightens every enemy for `PowerSettings::frightened_secs`.
+//! Frightened enemies can be eaten for 200, 400, 800 and then 1600 points, and
+//! are sent back to their pen for a while before they rejoin the game.
+
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::collision::{check_for_collisions, snake_eating};
+use crate::components::{Enemy, Player};
+use crate::ghost::{ghost_movement, Personality};
+use crate::grid::{meet, Direction, Position, PreviousPosition};
+use crate::level::Level;
+use crate::lives::check_for_ghost_contact;
+use crate::scoring::ScoreEvent;
+use crate::state::AppState;
+use crate::GameMode;
+
+const FRIGHTENED_COLOR: Color = Color::rgb(0.2, 0.2, 1.0);
+/// Seconds between two blinks once the frightened time is running out.
+const BLINK_PERIOD: f32 = 0.25;
+/// Seconds an eaten enemy stays in its pen.
+const RESPAWN_SECS: f32 = 3.0;
+
+pub struct PowerPlugin(pub GameMode);
+
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PowerPelletEaten>()
+            .add_event::<GhostEatenEvent>()
+            .init_resource::<PowerSettings>()
+            .init_resource::<PowerState>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_power))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(frighten_enemies)
+                    .with_system(update_power.after(frighten_enemies))
+                    .with_system(update_respawning),
+            )
+            .add_system(update_enemy_colors);
+        match self.0 {
+            GameMode::Pacman => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        .with_system(eat_frightened_enemies.after(check_for_collisions)),
+                );
+            }
+            GameMode::Packman2 => {
+                app.add_system_set(
+                    SystemSet::on_update(AppState::Playing)
+                        // so that eating a frightened ghost wins over dying to it
+                        .with_system(
+                            eat_frightened_ghosts
+                                .after(snake_eating)
+                                .after(ghost_movement)
+                                .before(check_for_ghost_contact),
+                        ),
+                );
+            }
+            GameMode::Fox | GameMode::FoxMaze => {}
+        }
+    }
+}
+
+/// How long a power pellet lasts.
+pub struct PowerSettings {
+    pub frightened_secs: f32,
+    /// The enemies blink during the last `warning_secs` of the frightened time.
+    pub warning_secs: f32,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            frightened_secs: 8.0,
+            warning_secs: 2.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PowerState {
+    timer: Option<Timer>,
+    ghosts_eaten: u32,
+}
+
+impl PowerState {
+    pub fn is_active(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.timer.as_ref().map_or(0., |timer| {
+            timer.duration().as_secs_f32() - timer.elapsed_secs()
+        })
+    }
+
+    /// Points for the next enemy eaten with the current pellet.
+    pub fn next_ghost_points(&self) -> u32 {
+        200 << self.ghosts_eaten.min(3)
+    }
+}
+
+/// Sent when the player eats a power pellet.
+#[derive(Default)]
+pub struct PowerPelletEaten;
+
+/// Sent when the player eats a frightened enemy.
+pub struct GhostEatenEvent {
+    pub points: u32,
+}
+
+/// Makes an enemy edible; on the grid it also wanders at random.
+#[derive(Component)]
+pub struct Frightened;
+
+/// An eaten enemy waiting in its pen.
+#[derive(Component)]
+pub struct Respawning(pub Timer);
+
+/// Where an enemy off the grid goes when it is eaten.
+#[derive(Component)]
+pub struct Home(pub Vec3);
+
+fn reset_power(mut state: ResMut<PowerState>) {
+    *state = PowerState::default();
+}
+
+fn frighten_enemies(
+    mut commands: Commands,
+    mut pellet_events: EventReader<PowerPelletEaten>,
+    settings: Res<PowerSettings>,
+    mut state: ResMut<PowerState>,
+    mut enemies: Query<(Entity, Option<&mut Direction>), (With<Enemy>, Without<Respawning>)>,
+) {
+    if pellet_events.iter().count() == 0 {
+        return;
+    }
+
+    state.timer = Some(Timer::from_seconds(settings.frightened_secs, false));
+    state.ghosts_eaten = 0;
+    for (entity, direction) in enemies.iter_mut() {
+        commands.entity(entity).insert(Frightened);
+        if let Some(mut direction) = direction {
+            *direction = direction.opposite();
+        }
+    }
+}
+
+fn update_power(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<PowerState>,
+    frightened: Query<Entity, With<Frightened>>,
+) {
+    let finished = match state.timer.as_mut() {
+        Some(timer) => timer.tick(time.delta()).finished(),
+        None => return,
+    };
+    if finished {
+        state.timer = None;
+        for entity in frightened.iter() {
+            commands.entity(entity).remove::<Frightened>();
+        }
+    }
+}
+
+fn update_respawning(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut respawning: Query<(Entity, &mut Respawning)>,
+) {
+    for (entity, mut respawning) in respawning.iter_mut() {
+        if respawning.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Respawning>();
+        }
+    }
+}
+
+fn eaten(
+    commands: &mut Commands,
+    entity: Entity,
+    state: &mut PowerState,
+    score_events: &mut EventWriter<ScoreEvent>,
+    ghost_events: &mut EventWriter<GhostEatenEvent>,
+) {
+    let points = state.next_ghost_points();
+    state.ghosts_eaten += 1;
+    score_events.send(ScoreEvent { points });
+    ghost_events.send(GhostEatenEvent { points });
+    commands
+        .entity(entity)
+        .remove::<Frightened>()
+        .insert(Respawning(Timer::from_seconds(RESPAWN_SECS, false)));
+}
+
+pub fn eat_frightened_enemies(
+    mut commands: Commands,
+    pacman_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(Entity, &mut Transform, &Home), (With<Enemy>, With<Frightened>)>,
+    mut state: ResMut<PowerState>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut ghost_events: EventWriter<GhostEatenEvent>,
+) {
+    let pacman_transform = pacman_query.single();
+    for (enemy_entity, mut enemy_transform, home) in enemy_query.iter_mut() {
+        let collision = collide(
+            pacman_transform.translation,
+            Vec2::splat(50.),
+            enemy_transform.translation,
+            Vec2::splat(50.),
+        );
+
+        if collision.is_some() {
+            enemy_transform.translation = home.0;
+            eaten(
+                &mut commands,
+                enemy_entity,
+                &mut state,
+                &mut score_events,
+                &mut ghost_events,
+            );
+        }
+    }
+}
+
+pub fn eat_frightened_ghosts(
+    mut commands: Commands,
+    level: Res<Level>,
+    heads: Query<(&Position, &PreviousPosition), (With<Player>, Without<Enemy>)>,
+    mut ghosts: Query<
+        (Entity, &mut Position, &PreviousPosition, &mut Direction),
+        (With<Enemy>, With<Frightened>),
+    >,
+    mut state: ResMut<PowerState>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut ghost_events: EventWriter<GhostEatenEvent>,
+) {
+    let pen = match level.ghost_starts().first() {
+        Some(pen) => *pen,
+        None => return,
+    };
+    for (head_pos, head_from) in heads.iter() {
+        for (entity, mut ghost_pos, ghost_from, mut direction) in ghosts.iter_mut() {
+            if meet(*head_pos, head_from.0, *ghost_pos, ghost_from.0) {
+                *ghost_pos = pen;
+                *direction = Direction::Up;
+                eaten(
+                    &mut commands,
+                    entity,
+                    &mut state,
+                    &mut score_events,
+                    &mut ghost_events,
+                );
+            }
+        }
+    }
+}
+
+fn update_enemy_colors(
+    state: Res<PowerState>,
+    settings: Res<PowerSettings>,
+    mut enemies: Query<(&mut Sprite, Option<&Personality>, Option<&Frightened>), With<Enemy>>,
+) {
+    let remaining = state.remaining_secs();
+    let blink_white =
+        remaining < settings.warning_secs && ((remaining / BLINK_PERIOD) as u32).is_multiple_of(2);
+    for (mut sprite, personality, frightened) in enemies.iter_mut() {
+        sprite.color = match (frightened, personality) {
+            (Some(_), _) if blink_white => Color::WHITE,
+            (Some(_), _) => FRIGHTENED_COLOR,
+            (None, Some(personality)) => personality.color(),
+            (None, None) => Color::WHITE,
+        };
+    }
+}
@@ -0,0 +1,176 @@
+//! Recording a session and playing it back.
+//!
+//! `--record <file>` saves the random seed, the options the game was started
+//! with, and the frame time and actions of every frame, when the game exits.
+//! `--replay <file>` starts the game with that seed and those options, and
+//! feeds the recorded actions and frame times back in place of live keyboard
+//! input and real frame times. Once the recording runs out, control goes back
+//! to the player.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{app::AppExit, prelude::*, time::TimeSystem};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{update_actions, Action, ActionInput, ActionState};
+use crate::cli;
+use crate::movement::PacmanMovement;
+use crate::rng::GameRng;
+use crate::snake::TailMode;
+use crate::time_attack::{TimeAttack, TimeAttackPlugin};
+use crate::GameMode;
+
+pub struct ReplayPlugin(pub GameMode);
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = cli::flag_value("replay") {
+            let replay = Replay::load(&path)
+                .unwrap_or_else(|err| panic!("could not load replay {}: {}", path, err));
+            if replay.mode != self.0 {
+                warn!("{} was recorded in {}", path, replay.mode.name());
+            }
+            // inserted before `RngPlugin`, which keeps it, and over the
+            // options the binary picked from its own flags
+            app.insert_resource(GameRng::new(replay.seed))
+                .insert_resource(replay.movement)
+                .insert_resource(replay.tail)
+                .insert_resource(ActionInput::Scripted)
+                .insert_resource(Playback {
+                    frames: replay.frames,
+                    next: 0,
+                })
+                .add_system_to_stage(CoreStage::First, play_back_time.after(TimeSystem))
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    play_back_actions.after(update_actions),
+                );
+            if replay.timed {
+                app.add_plugin(TimeAttackPlugin(self.0));
+            }
+        } else if let Some(path) = cli::flag_value("record") {
+            app.insert_resource(Recording {
+                path: PathBuf::from(path),
+                mode: self.0,
+                frames: Vec::new(),
+            })
+            .add_system_to_stage(CoreStage::PreUpdate, record_frame.after(update_actions))
+            .add_system_to_stage(CoreStage::Last, save_recording);
+        }
+    }
+}
+
+/// Contents of a replay file.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub mode: GameMode,
+    pub seed: u64,
+    /// How pacman was steered; `--grid-locked`.
+    #[serde(default)]
+    pub movement: PacmanMovement,
+    /// Whether the snake grew a tail; `--snake`.
+    #[serde(default)]
+    pub tail: TailMode,
+    /// Whether it was a time attack; `--timed`.
+    #[serde(default)]
+    pub timed: bool,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Time since the previous frame.
+    pub delta: Duration,
+    pub actions: Vec<Action>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let contents = ron::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
+
+struct Recording {
+    path: PathBuf,
+    mode: GameMode,
+    frames: Vec<ReplayFrame>,
+}
+
+struct Playback {
+    frames: Vec<ReplayFrame>,
+    next: usize,
+}
+
+impl Playback {
+    fn current(&self) -> Option<&ReplayFrame> {
+        self.frames.get(self.next)
+    }
+}
+
+fn record_frame(time: Res<Time>, actions: Res<ActionState>, mut recording: ResMut<Recording>) {
+    recording.frames.push(ReplayFrame {
+        delta: time.delta(),
+        actions: actions.pressed_actions(),
+    });
+}
+
+fn save_recording(
+    exit_events: EventReader<AppExit>,
+    rng: Res<GameRng>,
+    movement: Option<Res<PacmanMovement>>,
+    tail: Option<Res<TailMode>>,
+    time_attack: Option<Res<TimeAttack>>,
+    recording: Res<Recording>,
+) {
+    if exit_events.is_empty() {
+        return;
+    }
+    let replay = Replay {
+        mode: recording.mode,
+        seed: rng.seed(),
+        movement: movement.map(|movement| *movement).unwrap_or_default(),
+        tail: tail.map(|tail| *tail).unwrap_or_default(),
+        timed: time_attack.is_some(),
+        frames: recording.frames.clone(),
+    };
+    match replay.save(&recording.path) {
+        Ok(()) => info!("saved the replay to {}", recording.path.display()),
+        Err(err) => warn!("could not save the replay: {}", err),
+    }
+}
+
+/// Makes this frame as long as the recorded one.
+fn play_back_time(mut time: ResMut<Time>, playback: Res<Playback>) {
+    if let (Some(frame), Some(last_update)) = (playback.current(), time.last_update()) {
+        let instant = last_update + frame.delta;
+        time.update_with_instant(instant);
+    }
+}
+
+fn play_back_actions(
+    mut playback: ResMut<Playback>,
+    mut input: ResMut<ActionInput>,
+    mut actions: ResMut<ActionState>,
+) {
+    match playback.current() {
+        Some(frame) => {
+            actions.set_pressed(frame.actions.iter().copied().collect());
+            playback.next += 1;
+        }
+        None if *input == ActionInput::Scripted => {
+            info!("end of the replay");
+            *input = ActionInput::Devices;
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,82 @@
+//! The random number generator behind every spawner and the ghosts.
+//!
+//! The seed comes from `--seed <n>` on the command line or the
+//! `PAC_HUMAN_SEED` environment variable, and is otherwise picked at random.
+//! It is logged at startup so that a run can be played again.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::cli;
+
+pub const SEED_ENV: &str = "PAC_HUMAN_SEED";
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        // an app that inserted its own generator, e.g. a test, keeps it
+        if !app.world.contains_resource::<GameRng>() {
+            app.insert_resource(GameRng::new(configured_seed()));
+        }
+        app.add_startup_system(log_seed);
+    }
+}
+
+/// Seeded generator shared by everything random in the game.
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+fn log_seed(rng: Res<GameRng>) {
+    info!(
+        "random seed: {} (pass --seed {} to play it again)",
+        rng.seed(),
+        rng.seed()
+    );
+}
+
+/// Seed from the command line, then the environment, then at random.
+fn configured_seed() -> u64 {
+    let configured = cli::flag_value("seed").or_else(|| std::env::var(SEED_ENV).ok());
+    match configured.map(|value| value.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(err)) => {
+            warn!("ignoring invalid seed: {}", err);
+            rand::random()
+        }
+        None => rand::random(),
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::components::BonusFruit;
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScoreBoard>()
+            .add_event::<ScoreEvent>()
+            .add_event::<FruitEaten>()
+            .add_system(add_score)
+            .add_system(count_fruit);
+    }
+}
+
+#[derive(Default)]
+pub struct ScoreBoard {
+    pub score: u32,
+    /// Best score so far, starting from the saved table; kept when a new game
+    /// starts.
+    pub high_score: u32,
+    pub fruit: u32,
+}
+
+/// Sent whenever the player earns points.
+pub struct ScoreEvent {
+    pub points: u32,
+}
+
+/// Sent when the player eats a piece of fruit, with the kind when it was the
+/// level's bonus fruit.
+pub struct FruitEaten(pub Option<BonusFruit>);
+
+fn add_score(mut score_events: EventReader<ScoreEvent>, mut scoreboard: ResMut<ScoreBoard>) {
+    for event in score_events.iter() {
+        scoreboard.score += event.points;
+        scoreboard.high_score = scoreboard.high_score.max(scoreboard.score);
+    }
+}
+
+fn count_fruit(mut fruit_events: EventReader<FruitEaten>, mut scoreboard: ResMut<ScoreBoard>) {
+    scoreboard.fruit += fruit_events.iter().count() as u32;
+}
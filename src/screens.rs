@@ -0,0 +1,504 @@
+//! Title, pause, level-clear and game-over screens, and the transitions
+//! between them.
+//!
+//! Every round starts on entering `AppState::Playing`, and the entities it
+//! spawned (marked `InGame`) are despawned when leaving the level-clear or
+//! game-over screen. A score that makes it into the high-score table asks
+//! for the player's initials before the game-over menu.
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::actions::{Action, ActionState};
+use crate::components::{InGame, Pellet, PowerPellet};
+use crate::highscores::{HighScores, INITIALS_LEN};
+use crate::level::CurrentLevel;
+use crate::lives::{Lives, STARTING_LIVES};
+use crate::scoring::ScoreBoard;
+use crate::state::AppState;
+use crate::GameMode;
+
+pub const SELECTED_COLOR: Color = Color::YELLOW;
+pub const UNSELECTED_COLOR: Color = Color::WHITE;
+/// Seconds the level-clear screen stays up.
+const LEVEL_CLEAR_SECS: f32 = 2.0;
+
+pub struct ScreensPlugin(pub GameMode);
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .init_resource::<PelletsOnBoard>()
+            .add_system(toggle_pause)
+            .add_system_set(SystemSet::on_enter(AppState::Title).with_system(spawn_title_screen))
+            .add_system_set(SystemSet::on_update(AppState::Title).with_system(start_game))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Title).with_system(despawn_with::<TitleScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause_screen))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Paused).with_system(despawn_with::<PauseScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::LevelClear).with_system(spawn_level_clear_screen),
+            )
+            .add_system_set(SystemSet::on_update(AppState::LevelClear).with_system(next_level))
+            .add_system_set(
+                SystemSet::on_exit(AppState::LevelClear)
+                    .with_system(despawn_with::<LevelClearScreen>)
+                    .with_system(despawn_with::<InGame>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(enter_game_over))
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver)
+                    .with_system(enter_initials)
+                    .with_system(game_over_menu),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver)
+                    .with_system(despawn_with::<InitialsScreen>)
+                    .with_system(despawn_with::<GameOverScreen>)
+                    .with_system(despawn_with::<InGame>)
+                    .with_system(reset_game),
+            );
+        if self.0 != GameMode::Fox {
+            app.add_system_set(
+                SystemSet::on_enter(AppState::Playing).with_system(reset_pellets_on_board),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(check_level_clear));
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct TitleScreen;
+
+#[derive(Component)]
+pub struct PauseScreen;
+
+#[derive(Component)]
+pub struct LevelClearScreen;
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[derive(Component)]
+pub struct InitialsScreen;
+
+/// Text showing the initials being entered, one section per letter.
+#[derive(Component)]
+pub struct InitialsText;
+
+/// Initials being entered for a new high score.
+struct Initials {
+    letters: [u8; INITIALS_LEN],
+    cursor: usize,
+    font: Handle<Font>,
+}
+
+/// Entries of the game-over menu, top to bottom.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameOverOption {
+    Retry,
+    Quit,
+}
+
+impl GameOverOption {
+    const ALL: [GameOverOption; 2] = [GameOverOption::Retry, GameOverOption::Quit];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Retry => "Retry",
+            Self::Quit => "Quit",
+        }
+    }
+}
+
+struct LevelClearTimer(Timer);
+
+/// Whether the pellets of the current round have been spawned yet.
+#[derive(Default)]
+struct PelletsOnBoard(bool);
+
+pub fn despawn_with<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Full-screen node that centres its children in a column.
+pub fn overlay(color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: bevy::ui::Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: color.into(),
+        ..default()
+    }
+}
+
+pub fn text(
+    value: impl Into<String>,
+    font: &Handle<Font>,
+    font_size: f32,
+    color: Color,
+) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        },
+    )
+}
+
+/// Adds the high-score table, one text per entry, to a screen.
+fn spawn_high_scores(parent: &mut ChildBuilder, font: &Handle<Font>, high_scores: &HighScores) {
+    parent.spawn_bundle(text("HIGH SCORES", font, 30., Color::YELLOW));
+    if high_scores.entries().is_empty() {
+        parent.spawn_bundle(text("---", font, 24., Color::WHITE));
+    }
+    for (rank, entry) in high_scores.entries().iter().enumerate() {
+        parent.spawn_bundle(text(
+            format!("{:>2}. {:<3} {:>7}", rank + 1, entry.initials, entry.score),
+            font,
+            24.,
+            Color::WHITE,
+        ));
+    }
+}
+
+fn spawn_title_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.8)))
+        .insert(TitleScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("PAC-HUMAN", &font, 100., Color::YELLOW));
+            parent.spawn_bundle(text("Press Enter to start", &font, 40., Color::WHITE));
+            parent.spawn_bundle(text("Press C for controls", &font, 30., Color::WHITE));
+            spawn_high_scores(parent, &font, &high_scores);
+        });
+}
+
+fn start_game(actions: Res<ActionState>, mut state: ResMut<State<AppState>>) {
+    if actions.just_pressed(Action::Confirm) {
+        let _ = state.set(AppState::Playing);
+    }
+}
+
+fn toggle_pause(actions: Res<ActionState>, mut state: ResMut<State<AppState>>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    let current = *state.current();
+    let _ = match current {
+        AppState::Playing => state.push(AppState::Paused),
+        AppState::Paused => state.pop(),
+        _ => return,
+    };
+}
+
+fn spawn_pause_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.5)))
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("PAUSED", &font, 80., Color::WHITE));
+            parent.spawn_bundle(text("Press P to resume", &font, 30., Color::WHITE));
+            parent.spawn_bundle(text("Press C for controls", &font, 30., Color::WHITE));
+        });
+}
+
+fn reset_pellets_on_board(mut on_board: ResMut<PelletsOnBoard>) {
+    on_board.0 = false;
+}
+
+/// Ends the level once every pellet has been eaten.
+fn check_level_clear(
+    pellets: Query<(), Or<(With<Pellet>, With<PowerPellet>)>>,
+    mut state: ResMut<State<AppState>>,
+    mut on_board: ResMut<PelletsOnBoard>,
+) {
+    // the pellets of a new round only show up once its spawn commands are applied
+    if !pellets.is_empty() {
+        on_board.0 = true;
+    } else if on_board.0 {
+        on_board.0 = false;
+        let _ = state.set(AppState::LevelClear);
+    }
+}
+
+fn spawn_level_clear_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.insert_resource(LevelClearTimer(Timer::from_seconds(
+        LEVEL_CLEAR_SECS,
+        false,
+    )));
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.5)))
+        .insert(LevelClearScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text(
+                format!("LEVEL {} CLEAR!", current_level.0),
+                &font,
+                80.,
+                Color::YELLOW,
+            ));
+        });
+}
+
+fn next_level(
+    time: Res<Time>,
+    mut timer: ResMut<LevelClearTimer>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if timer.0.tick(time.delta()).just_finished() {
+        current_level.0 += 1;
+        let _ = state.set(AppState::Playing);
+    }
+}
+
+fn enter_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<ScoreBoard>,
+    high_scores: Res<HighScores>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    if high_scores.qualifies(scoreboard.score) {
+        spawn_initials_screen(&mut commands, &font);
+    } else {
+        spawn_game_over_screen(&mut commands, &font, &scoreboard, &high_scores);
+    }
+}
+
+fn spawn_initials_screen(commands: &mut Commands, font: &Handle<Font>) {
+    let initials = Initials {
+        letters: [b'A'; INITIALS_LEN],
+        cursor: 0,
+        font: font.clone(),
+    };
+    let style = TextStyle {
+        font: font.clone(),
+        font_size: 80.,
+        color: UNSELECTED_COLOR,
+    };
+    let sections = initials
+        .letters
+        .iter()
+        .map(|letter| TextSection::new(char::from(*letter), style.clone()));
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.7)))
+        .insert(InitialsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("NEW HIGH SCORE!", font, 80., Color::YELLOW));
+            parent.spawn_bundle(text("Enter your initials", font, 40., Color::WHITE));
+            parent
+                .spawn_bundle(TextBundle::from_sections(sections))
+                .insert(InitialsText);
+        });
+    commands.insert_resource(initials);
+}
+
+/// Arcade-style entry: up and down change the letter, left and right move
+/// between letters, Enter moves on to the next one, and the last one saves
+/// the score.
+fn enter_initials(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    scoreboard: Res<ScoreBoard>,
+    mut high_scores: ResMut<HighScores>,
+    initials: Option<ResMut<Initials>>,
+    mut texts: Query<&mut Text, With<InitialsText>>,
+    screens: Query<Entity, With<InitialsScreen>>,
+) {
+    let mut initials = match initials {
+        Some(initials) => initials,
+        None => return,
+    };
+    let cursor = initials.cursor;
+    let letter = &mut initials.letters[cursor];
+    if actions.just_pressed(Action::MoveUp) {
+        *letter = if *letter == b'Z' { b'A' } else { *letter + 1 };
+    }
+    if actions.just_pressed(Action::MoveDown) {
+        *letter = if *letter == b'A' { b'Z' } else { *letter - 1 };
+    }
+    if actions.just_pressed(Action::MoveLeft) {
+        initials.cursor = initials.cursor.saturating_sub(1);
+    }
+    if actions.just_pressed(Action::MoveRight) {
+        initials.cursor = (initials.cursor + 1).min(INITIALS_LEN - 1);
+    }
+    let confirmed = actions.just_pressed(Action::Confirm);
+    if confirmed && initials.cursor + 1 < INITIALS_LEN {
+        initials.cursor += 1;
+    } else if confirmed {
+        let name: String = initials
+            .letters
+            .iter()
+            .map(|&letter| char::from(letter))
+            .collect();
+        high_scores.insert(name, scoreboard.score);
+        if let Err(err) = high_scores.save() {
+            warn!("could not save the high scores: {}", err);
+        }
+        commands.remove_resource::<Initials>();
+        for entity in screens.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_game_over_screen(&mut commands, &initials.font, &scoreboard, &high_scores);
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        for (i, section) in text.sections.iter_mut().enumerate() {
+            section.value = char::from(initials.letters[i]).to_string();
+            section.style.color = if i == initials.cursor {
+                SELECTED_COLOR
+            } else {
+                UNSELECTED_COLOR
+            };
+        }
+    }
+}
+
+fn spawn_game_over_screen(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    scoreboard: &ScoreBoard,
+    high_scores: &HighScores,
+) {
+    commands
+        .spawn_bundle(overlay(Color::rgba(0., 0., 0., 0.7)))
+        .insert(GameOverScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("GAME OVER", font, 80., Color::RED));
+            parent.spawn_bundle(text(
+                format!("Final score: {}", scoreboard.score),
+                font,
+                40.,
+                Color::WHITE,
+            ));
+            for (i, option) in GameOverOption::ALL.into_iter().enumerate() {
+                let color = if i == 0 {
+                    SELECTED_COLOR
+                } else {
+                    UNSELECTED_COLOR
+                };
+                parent
+                    .spawn_bundle(text(option.label(), font, 40., color))
+                    .insert(option);
+            }
+            spawn_high_scores(parent, font, high_scores);
+        });
+}
+
+fn game_over_menu(
+    actions: Res<ActionState>,
+    initials: Option<Res<Initials>>,
+    mut selected: Local<usize>,
+    mut options: Query<(&GameOverOption, &mut Text)>,
+    mut state: ResMut<State<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if initials.is_some() {
+        return;
+    }
+    let count = GameOverOption::ALL.len();
+    if actions.just_pressed(Action::MoveUp) {
+        *selected = (*selected + count - 1) % count;
+    }
+    if actions.just_pressed(Action::MoveDown) {
+        *selected = (*selected + 1) % count;
+    }
+    let current = GameOverOption::ALL[*selected];
+    for (option, mut text) in options.iter_mut() {
+        text.sections[0].style.color = if *option == current {
+            SELECTED_COLOR
+        } else {
+            UNSELECTED_COLOR
+        };
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        *selected = 0;
+        match current {
+            GameOverOption::Retry => {
+                let _ = state.set(AppState::Playing);
+            }
+            GameOverOption::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+fn reset_game(
+    mut scoreboard: ResMut<ScoreBoard>,
+    mut lives: Option<ResMut<Lives>>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    scoreboard.score = 0;
+    scoreboard.fruit = 0;
+    if let Some(lives) = lives.as_mut() {
+        lives.0 = STARTING_LIVES;
+    }
+    current_level.0 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, action: Action) -> usize {
+        let mut actions = app.world.resource_mut::<ActionState>();
+        actions.set_pressed([action].into_iter().collect());
+        app.update();
+        app.world
+            .resource_mut::<ActionState>()
+            .set_pressed(Default::default());
+        app.update();
+        app.world.resource::<Initials>().cursor
+    }
+
+    #[test]
+    fn the_initials_cursor_moves_both_ways_within_the_letters() {
+        let mut app = App::new();
+        app.init_resource::<ActionState>()
+            .init_resource::<ScoreBoard>()
+            .insert_resource(HighScores::in_memory())
+            .insert_resource(Initials {
+                letters: [b'A'; INITIALS_LEN],
+                cursor: 1,
+                font: Handle::default(),
+            })
+            .add_system(enter_initials);
+
+        assert_eq!(press(&mut app, Action::MoveLeft), 0);
+        assert_eq!(press(&mut app, Action::MoveRight), 1);
+        for _ in 0..INITIALS_LEN {
+            press(&mut app, Action::MoveRight);
+        }
+        assert_eq!(app.world.resource::<Initials>().cursor, INITIALS_LEN - 1);
+        // moving back over a letter leaves it as it was
+        assert_eq!(
+            app.world.resource::<Initials>().letters,
+            [b'A'; INITIALS_LEN]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! The snake's tail in packman2 (`packman2 --snake`).
+//!
+//! With `TailMode::Growing`, every `GrowthEvent` adds a `SnakeSegment` at the
+//! end of the tail, and the tail follows the head cell by cell. A longer
+//! tail is worth more on every bite, and running into it ends the game.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{snake_eating, GrowthEvent};
+use crate::components::{InGame, Player, Size};
+use crate::grid::Position;
+use crate::movement::{snake_movement, HeadStepped};
+use crate::scoring::ScoreEvent;
+use crate::state::AppState;
+
+const SEGMENT_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+/// Points per segment of the tail, scored whenever it grows.
+const POINTS_PER_SEGMENT: u32 = 10;
+
+pub struct SnakePlugin;
+
+impl Plugin for SnakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TailMode>()
+            .init_resource::<SnakeSegments>()
+            .init_resource::<LastTailPosition>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(forget_tail))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(follow_head.after(snake_movement))
+                    .with_system(grow_tail.after(snake_eating).after(follow_head))
+                    .with_system(bite_tail.after(follow_head)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Dying).with_system(drop_tail));
+    }
+}
+
+/// Whether the snake grows a tail.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TailMode {
+    /// Just the head, as in Pac-Man.
+    #[default]
+    None,
+    /// Eating grows the tail.
+    Growing,
+}
+
+#[derive(Component)]
+pub struct SnakeSegment;
+
+/// Segments of the tail, from the head back.
+#[derive(Default)]
+pub struct SnakeSegments(pub Vec<Entity>);
+
+/// Cell the end of the tail last left, where the next segment goes.
+#[derive(Default)]
+struct LastTailPosition(Option<Position>);
+
+/// The segments of the last round were despawned with everything else.
+fn forget_tail(mut segments: ResMut<SnakeSegments>, mut last: ResMut<LastTailPosition>) {
+    segments.0.clear();
+    last.0 = None;
+}
+
+/// Moves every segment on to the cell of the one before it, for every step
+/// of the head.
+fn follow_head(
+    mut step_events: EventReader<HeadStepped>,
+    segments: Res<SnakeSegments>,
+    mut last: ResMut<LastTailPosition>,
+    mut positions: Query<&mut Position, With<SnakeSegment>>,
+) {
+    for step in step_events.iter() {
+        let mut vacated = step.from;
+        for entity in &segments.0 {
+            if let Ok(mut pos) = positions.get_mut(*entity) {
+                vacated = std::mem::replace(&mut *pos, vacated);
+            }
+        }
+        last.0 = Some(vacated);
+    }
+}
+
+fn grow_tail(
+    mut commands: Commands,
+    mode: Res<TailMode>,
+    mut growth_events: EventReader<GrowthEvent>,
+    mut segments: ResMut<SnakeSegments>,
+    last: Res<LastTailPosition>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    if *mode == TailMode::None {
+        growth_events.clear();
+        return;
+    }
+    let pos = match last.0 {
+        Some(pos) => pos,
+        None => return,
+    };
+    for _ in growth_events.iter() {
+        let segment = commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: SEGMENT_COLOR,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(SnakeSegment)
+            .insert(InGame)
+            .insert(pos)
+            .insert(Size::square(0.8))
+            .id();
+        segments.0.push(segment);
+        score_events.send(ScoreEvent {
+            points: POINTS_PER_SEGMENT * segments.0.len() as u32,
+        });
+    }
+}
+
+fn bite_tail(
+    heads: Query<&Position, With<Player>>,
+    segments: Query<&Position, With<SnakeSegment>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for head in heads.iter() {
+        if segments.iter().any(|pos| pos == head) {
+            let _ = state.set(AppState::GameOver);
+            return;
+        }
+    }
+}
+
+/// Starts the next life with just the head.
+fn drop_tail(
+    mut commands: Commands,
+    mut segments: ResMut<SnakeSegments>,
+    mut last: ResMut<LastTailPosition>,
+) {
+    for entity in segments.0.drain(..) {
+        commands.entity(entity).despawn();
+    }
+    last.0 = None;
+}
@@ -0,0 +1,481 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::system::EntityCommands, math::Vec3Swizzles, prelude::*};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+use crate::collision::{check_for_collisions_with_fox, CollisionEvent, GroundBox, ARENA_SIZE};
+use crate::components::{
+    BonusFruit, Collider, Enemy, Food, Fruit, InGame, Obstacle, Player, Points, PowerPellet, Size,
+};
+use crate::ghost::{ghost_movement, Personality};
+use crate::grid::{Direction, Position, PreviousPosition};
+use crate::level::Level;
+use crate::maze;
+use crate::movement::{snake_movement, Heading};
+use crate::power::Home;
+use crate::rng::GameRng;
+use crate::snake::SnakeSegment;
+use crate::speed::{tune_speeds, FrightenedSpeed, LevelSpeeds, Speed, StepProgress, TunnelSpeed};
+use crate::state::{playing_every, AppState};
+use crate::GameMode;
+
+pub struct SpawningPlugin(pub GameMode);
+
+impl Plugin for SpawningPlugin {
+    fn build(&self, app: &mut App) {
+        match self.0 {
+            GameMode::Pacman => {
+                app.add_system_set(
+                    SystemSet::on_enter(AppState::Playing)
+                        .with_system(spawn_pacman.after(tune_speeds))
+                        .with_system(spawn_enemies.after(tune_speeds))
+                        .with_system(spawn_power_pellets),
+                );
+            }
+            GameMode::Fox => {
+                app.init_resource::<PickupSpread>()
+                    .add_system_set(
+                        SystemSet::on_enter(AppState::Playing)
+                            .with_system(spawn_fox.after(tune_speeds))
+                            .with_system(spawn_obstacles)
+                            .with_system(spawn_first_cube),
+                    )
+                    .add_system_set(
+                        SystemSet::on_update(AppState::Playing)
+                            .with_system(respawn_cube.after(check_for_collisions_with_fox)),
+                    );
+            }
+            GameMode::Packman2 => {
+                app.init_resource::<FruitSettings>().add_system_set(
+                    SystemSet::new()
+                        .with_run_criteria(playing_every(3.0))
+                        // so that nobody moves on to the fruit as it lands
+                        .with_system(food_spawner.after(snake_movement).after(ghost_movement))
+                        // every draw from `GameRng` in a fixed order, for the seed to replay
+                        .with_system(enemy_spawner.after(food_spawner)),
+                );
+            }
+            GameMode::FoxMaze => {
+                // the fox is spawned with the maze, at its start
+                app.add_system_set(
+                    SystemSet::new()
+                        .with_run_criteria(playing_every(3.0))
+                        .with_system(ghost_pursuer_spawner.after(ghost_movement)),
+                );
+            }
+        }
+    }
+}
+
+fn spawn_pacman(mut commands: Commands, asset_server: Res<AssetServer>, speeds: Res<LevelSpeeds>) {
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            texture: asset_server.load("images/pacman.png"),
+            transform: Transform::from_translation(Vec3::new(0., 0., 0.))
+                .with_scale(Vec3::new(0.1, 0.1, 0.)),
+            ..default()
+        })
+        .insert(Player)
+        .insert(Heading::default())
+        .insert(Speed(speeds.0.pacman))
+        .insert(InGame);
+}
+
+fn spawn_enemies(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    speeds: Res<LevelSpeeds>,
+    mut rng: ResMut<GameRng>,
+) {
+    for i in 0..10 {
+        // keep clear of pacman's starting point
+        let enemy_translation = loop {
+            let x: f32 = rng.gen_range(-300.0..300.0);
+            let y: f32 = rng.gen_range(-300.0..300.0);
+            let translation = Vec3::new(x, y, 0.0);
+            if translation.length() > 150. {
+                break translation;
+            }
+        };
+
+        commands
+            .spawn()
+            .insert_bundle(SpriteBundle {
+                texture: asset_server.load("images/enemy.png"),
+                transform: Transform {
+                    translation: enemy_translation,
+                    scale: Vec3::new(0.1, 0.1, 0.),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(Enemy)
+            .insert(InGame)
+            .insert(Personality::ALL[i % Personality::ALL.len()])
+            .insert(Speed(speeds.0.pacman_enemies))
+            .insert(FrightenedSpeed(speeds.0.pacman_enemies_frightened))
+            .insert(Home(enemy_translation));
+    }
+}
+
+fn spawn_power_pellets(mut commands: Commands) {
+    for (x, y) in [(-250., 250.), (250., 250.), (-250., -250.), (250., -250.)] {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(1.0, 0.9, 0.6),
+                    custom_size: Some(Vec2::splat(20.)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, y, 0.),
+                ..default()
+            })
+            .insert(PowerPellet)
+            .insert(Food)
+            .insert(InGame)
+            .insert(Points(50))
+            .insert(Size::square(20.));
+    }
+}
+
+/// Ground the fox model covers at a scale of 1.
+pub const FOX_SIZE: Vec3 = Vec3::new(25., 70., 125.);
+
+fn spawn_fox(mut commands: Commands, asset_server: Res<AssetServer>, speeds: Res<LevelSpeeds>) {
+    spawn_fox_at(
+        &mut commands,
+        &asset_server,
+        speeds.0.fox_walk,
+        Transform::default(),
+    );
+}
+
+/// Spawns the fox at `transform`, its `Size` scaled along with the model.
+pub fn spawn_fox_at<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    asset_server: &AssetServer,
+    speed: f32,
+    transform: Transform,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut fox = commands.spawn();
+    fox.insert_bundle(SceneBundle {
+        scene: asset_server.load("models/Fox.glb#Scene0"),
+        transform,
+        ..default()
+    })
+    .insert(Player)
+    .insert(InGame)
+    .insert(Speed(speed))
+    .insert(Size(FOX_SIZE * transform.scale));
+    fox
+}
+
+/// Part of the arena, from the middle out, that new cubes show up in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PickupSpread(pub f32);
+
+impl Default for PickupSpread {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+/// Centre and size of the walls in the fox's arena.
+const OBSTACLES: [(Vec3, Vec3); 4] = [
+    (Vec3::new(-250., 50., 0.), Vec3::new(40., 100., 400.)),
+    (Vec3::new(250., 50., 0.), Vec3::new(40., 100., 400.)),
+    (Vec3::new(0., 50., -300.), Vec3::new(300., 100., 40.)),
+    (Vec3::new(0., 50., 300.), Vec3::new(300., 100., 40.)),
+];
+/// Size of the cubes the fox collects.
+const CUBE_SIZE: f32 = 100.;
+/// Width and height of the ghosts in the maze.
+const GHOST_SIZE: f32 = 30.;
+
+fn spawn_obstacles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (center, size) in OBSTACLES {
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: materials.add(Color::DARK_GRAY.into()),
+                transform: Transform::from_translation(center),
+                ..default()
+            })
+            .insert(Obstacle)
+            .insert(InGame)
+            .insert(Size(size));
+    }
+}
+
+fn spawn_cube(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    cube_translation: Vec3,
+) {
+    commands
+        .spawn()
+        .insert_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: CUBE_SIZE })),
+            material: materials.add(Color::BLUE.into()),
+            transform: Transform::from_translation(cube_translation),
+            ..default()
+        })
+        .insert(Collider)
+        .insert(InGame)
+        .insert(Points(1))
+        .insert(Size(Vec3::splat(CUBE_SIZE)));
+}
+
+fn spawn_first_cube(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    spread: Res<PickupSpread>,
+) {
+    let translation = free_cube_translation(&mut rng, &spread);
+    spawn_cube(&mut commands, &mut meshes, &mut materials, translation);
+}
+
+pub fn respawn_cube(
+    mut commands: Commands,
+    collision_events: EventReader<CollisionEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    spread: Res<PickupSpread>,
+) {
+    if !collision_events.is_empty() {
+        let translation = free_cube_translation(&mut rng, &spread);
+        spawn_cube(&mut commands, &mut meshes, &mut materials, translation);
+
+        collision_events.clear();
+    }
+}
+
+/// A random spot for a cube within `spread` of the arena, on the ground and
+/// out of the walls.
+fn free_cube_translation(rng: &mut GameRng, spread: &PickupSpread) -> Vec3 {
+    let limit = (ARENA_SIZE - CUBE_SIZE) / 2. * spread.0;
+    loop {
+        let x = rng.gen_range(-limit..limit);
+        let z = rng.gen_range(-limit..limit);
+        let cube = GroundBox::axis_aligned(Vec2::new(x, z), Vec2::splat(CUBE_SIZE));
+        let blocked = OBSTACLES
+            .iter()
+            .any(|(center, size)| cube.overlaps(&GroundBox::axis_aligned(center.xz(), size.xz())));
+        if !blocked {
+            break Vec3::new(x, 50., z);
+        }
+    }
+}
+
+/// A kind of fruit `food_spawner` drops.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FruitKind {
+    pub points: u32,
+    /// How often this kind is picked, relative to the others.
+    pub weight: u32,
+    /// Tint of the fruit sprite.
+    pub color: Color,
+}
+
+/// What `food_spawner` drops in packman2.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FruitSettings {
+    /// No fruit is dropped while this many are on the board.
+    pub max_on_board: usize,
+    pub kinds: Vec<FruitKind>,
+}
+
+impl Default for FruitSettings {
+    fn default() -> Self {
+        Self {
+            max_on_board: 3,
+            kinds: vec![
+                FruitKind {
+                    points: 100,
+                    weight: 6,
+                    color: Color::WHITE,
+                },
+                FruitKind {
+                    points: 300,
+                    weight: 3,
+                    color: Color::ORANGE,
+                },
+                FruitKind {
+                    points: 1000,
+                    weight: 1,
+                    color: Color::GOLD,
+                },
+            ],
+        }
+    }
+}
+
+/// Drops a fruit on a free cell the player can walk to.
+#[allow(clippy::too_many_arguments)]
+pub fn food_spawner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Option<Res<Level>>,
+    settings: Res<FruitSettings>,
+    mut rng: ResMut<GameRng>,
+    fruit: Query<(), (With<Fruit>, Without<BonusFruit>)>,
+    player: Query<&Position, With<Player>>,
+    occupied: Query<&Position, Or<(With<Player>, With<Enemy>, With<SnakeSegment>, With<Fruit>)>>,
+) {
+    // `load_level` may not have run yet on the first frame of a round
+    let level = match level {
+        Some(level) => level,
+        None => return,
+    };
+    if fruit.iter().count() >= settings.max_on_board {
+        return;
+    }
+    let start = match player.get_single() {
+        Ok(pos) => *pos,
+        Err(_) => return,
+    };
+    let taken: HashSet<Position> = occupied.iter().copied().collect();
+    let free: Vec<Position> = level
+        .reachable_from(start)
+        .into_iter()
+        .filter(|pos| !taken.contains(pos))
+        .collect();
+    if free.is_empty() {
+        return;
+    }
+    let kind = match WeightedIndex::new(settings.kinds.iter().map(|kind| kind.weight)) {
+        Ok(weights) => &settings.kinds[weights.sample(&mut *rng)],
+        Err(_) => return,
+    };
+    let pos = free[rng.gen_range(0..free.len())];
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: kind.color,
+                ..default()
+            },
+            texture: asset_server.load("images/fruit.png"),
+            ..default()
+        })
+        .insert(Food)
+        .insert(Fruit)
+        .insert(InGame)
+        .insert(Points(kind.points))
+        .insert(pos)
+        .insert(Size::square(0.8));
+}
+
+/// Releases the next ghost that is not on the board yet.
+pub fn enemy_spawner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Option<Res<Level>>,
+    speeds: Res<LevelSpeeds>,
+    mut rng: ResMut<GameRng>,
+    ghosts: Query<&Personality, With<Enemy>>,
+) {
+    let level = match level {
+        Some(level) => level,
+        None => return,
+    };
+    let (personality, pos) = match next_ghost(&level, &mut rng, &ghosts) {
+        Some(ghost) => ghost,
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: personality.color(),
+                ..default()
+            },
+            texture: asset_server.load("images/enemy.png"),
+            ..default()
+        })
+        .insert(Enemy)
+        .insert(InGame)
+        .insert(personality)
+        .insert(Direction::Up)
+        .insert(Speed(speeds.0.ghost(personality)))
+        .insert(FrightenedSpeed(speeds.0.frightened))
+        .insert(TunnelSpeed(speeds.0.tunnel))
+        .insert(StepProgress::default())
+        .insert(pos)
+        .insert(PreviousPosition(pos))
+        .insert(Size::square(0.9));
+}
+
+/// Releases the next ghost of the maze that is not out yet.
+pub fn ghost_pursuer_spawner(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Option<Res<Level>>,
+    speeds: Res<LevelSpeeds>,
+    mut rng: ResMut<GameRng>,
+    ghosts: Query<&Personality, With<Enemy>>,
+) {
+    let level = match level {
+        Some(level) => level,
+        None => return,
+    };
+    let (personality, pos) = match next_ghost(&level, &mut rng, &ghosts) {
+        Some(ghost) => ghost,
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Capsule {
+                radius: GHOST_SIZE / 2.,
+                depth: GHOST_SIZE / 2.,
+                ..default()
+            })),
+            material: materials.add(personality.color().into()),
+            transform: Transform::from_translation(maze::cell_center(&level, pos)),
+            ..default()
+        })
+        .insert(Enemy)
+        .insert(InGame)
+        .insert(personality)
+        .insert(Direction::Up)
+        .insert(Speed(speeds.0.ghost(personality)))
+        .insert(FrightenedSpeed(speeds.0.frightened))
+        .insert(TunnelSpeed(speeds.0.tunnel))
+        .insert(StepProgress::default())
+        .insert(pos)
+        .insert(Size(Vec3::new(GHOST_SIZE, GHOST_SIZE * 1.5, GHOST_SIZE)));
+}
+
+/// The first ghost in `Personality::ALL` that is not on the board, and the pen
+/// it starts from.
+fn next_ghost(
+    level: &Level,
+    rng: &mut GameRng,
+    ghosts: &Query<&Personality, With<Enemy>>,
+) -> Option<(Personality, Position)> {
+    let personality = Personality::ALL
+        .into_iter()
+        .find(|personality| !ghosts.iter().any(|ghost| ghost == personality))?;
+    let ghost_starts = level.ghost_starts();
+    if ghost_starts.is_empty() {
+        return None;
+    }
+    Some((
+        personality,
+        ghost_starts[rng.gen_range(0..ghost_starts.len())],
+    ))
+}
@@ -0,0 +1,131 @@
+//! Movement speeds, in units per second.
+//!
+//! Pacman and the fox move in pixels per second, actors on the `Position`
+//! grid in cells per second. `SpeedSettings` holds the speeds of the first
+//! level; on entering `AppState::Playing` they are scaled for the level into
+//! `LevelSpeeds`, from which every moving entity gets its `Speed` when it is
+//! spawned. Ghosts also carry their own `FrightenedSpeed` and `TunnelSpeed`.
+
+use bevy::prelude::*;
+
+use crate::ghost::Personality;
+use crate::level::CurrentLevel;
+use crate::state::AppState;
+
+/// How much faster everything gets with every level.
+const LEVEL_SPEEDUP: f32 = 0.05;
+/// Upper bound of the level speed-up.
+const MAX_SPEEDUP: f32 = 1.25;
+
+pub struct SpeedPlugin;
+
+impl Plugin for SpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedSettings>()
+            .init_resource::<LevelSpeeds>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(tune_speeds));
+    }
+}
+
+/// Normal speed of an entity.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct Speed(pub f32);
+
+/// Speed of a ghost while it is frightened.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct FrightenedSpeed(pub f32);
+
+/// Speed of a ghost in a tunnel.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct TunnelSpeed(pub f32);
+
+/// Part of the next cell a grid actor has already covered.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default)]
+pub struct StepProgress(pub f32);
+
+impl StepProgress {
+    /// Covers `cells` more and returns how many whole cells to step.
+    pub fn advance(&mut self, cells: f32) -> u32 {
+        self.0 += cells;
+        let steps = self.0.floor();
+        self.0 -= steps;
+        steps as u32
+    }
+}
+
+/// Speeds of the first level. Insert your own to tune the game; they are
+/// never overwritten.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpeedSettings {
+    /// Pixels per second.
+    pub pacman: f32,
+    /// Pixels per second of pacman's enemies.
+    pub pacman_enemies: f32,
+    /// Pixels per second of pacman's enemies while frightened.
+    pub pacman_enemies_frightened: f32,
+    /// Pixels per second while walking.
+    pub fox_walk: f32,
+    /// Pixels per second while running.
+    pub fox_run: f32,
+    /// Cells per second.
+    pub snake: f32,
+    /// Cells per second of each ghost, in the order of `Personality::ALL`.
+    pub ghosts: [f32; 4],
+    /// Cells per second of a frightened ghost.
+    pub frightened: f32,
+    /// Cells per second of a ghost in a tunnel.
+    pub tunnel: f32,
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        Self {
+            pacman: 300.,
+            pacman_enemies: 150.,
+            pacman_enemies_frightened: 75.,
+            fox_walk: 180.,
+            fox_run: 600.,
+            snake: 1. / 0.15,
+            ghosts: [5., 5., 5., 4.75],
+            frightened: 2.5,
+            tunnel: 2.5,
+        }
+    }
+}
+
+impl SpeedSettings {
+    /// These speeds sped up by `LEVEL_SPEEDUP` for every level after the
+    /// first. Frightened and tunnel speeds stay the same.
+    pub fn for_level(&self, level: u32) -> Self {
+        let factor = (1. + LEVEL_SPEEDUP * level.saturating_sub(1) as f32).min(MAX_SPEEDUP);
+        Self {
+            pacman: self.pacman * factor,
+            pacman_enemies: self.pacman_enemies * factor,
+            fox_walk: self.fox_walk * factor,
+            fox_run: self.fox_run * factor,
+            snake: self.snake * factor,
+            ghosts: self.ghosts.map(|speed| speed * factor),
+            ..self.clone()
+        }
+    }
+
+    pub fn ghost(&self, personality: Personality) -> f32 {
+        let index = Personality::ALL
+            .iter()
+            .position(|other| *other == personality)
+            .unwrap();
+        self.ghosts[index]
+    }
+}
+
+/// `SpeedSettings` tuned for the level being played.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LevelSpeeds(pub SpeedSettings);
+
+pub fn tune_speeds(
+    current_level: Res<CurrentLevel>,
+    settings: Res<SpeedSettings>,
+    mut speeds: ResMut<LevelSpeeds>,
+) {
+    speeds.0 = settings.for_level(current_level.0);
+}
@@ -0,0 +1,43 @@
+use bevy::{
+    ecs::{schedule::ShouldRun, system::IntoChainSystem},
+    prelude::*,
+    time::FixedTimestep,
+};
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(AppState::Title);
+    }
+}
+
+/// Which screen the game is on.
+///
+/// Gameplay systems only run in `Playing`. `Paused` and `Dying` are pushed on
+/// top of `Playing`, so leaving them resumes the round where it was, while
+/// every other transition uses `set` and starts a new round on entering
+/// `Playing`. `Controls` is pushed on top of `Title` or `Paused`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum AppState {
+    Title,
+    Playing,
+    Paused,
+    Dying,
+    LevelClear,
+    GameOver,
+    Controls,
+}
+
+/// Run criteria for gameplay systems that step every `step` seconds.
+pub fn playing_every(step: f64) -> impl System<In = (), Out = ShouldRun> {
+    FixedTimestep::step(step).chain(only_while_playing)
+}
+
+fn only_while_playing(In(should_run): In<ShouldRun>, state: Res<State<AppState>>) -> ShouldRun {
+    if *state.current() == AppState::Playing {
+        should_run
+    } else {
+        ShouldRun::No
+    }
+}
@@ -0,0 +1,349 @@
+//! Time attack for the fox (`fox --timed`).
+//!
+//! The run starts with `TimeAttackSettings::start_secs` on the clock and
+//! every pickup adds `bonus_secs`. Cubes show up near the middle of the arena
+//! at first and spread out over all of it as the run goes on. When the clock
+//! runs out the game is over, and the results are shown under the game-over
+//! screen. The best run of each `GameMode` is kept in
+//! `<data dir>/pac-human/time-attack-<mode>.ron`.
+
+use std::{fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{check_for_collisions_with_fox, CollisionEvent};
+use crate::screens::{despawn_with, text};
+use crate::spawning::PickupSpread;
+use crate::state::AppState;
+use crate::GameMode;
+
+pub struct TimeAttackPlugin(pub GameMode);
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeAttackSettings>()
+            .init_resource::<TimeAttack>()
+            .insert_resource(BestRun::load(self.0))
+            .add_startup_system(reset_time_attack)
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(add_bonus_time.after(check_for_collisions_with_fox))
+                    .with_system(count_down.after(add_bonus_time))
+                    .with_system(widen_spread.after(count_down)),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(show_results))
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver)
+                    .with_system(despawn_with::<ResultsPanel>)
+                    .with_system(reset_time_attack),
+            );
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimeAttackSettings {
+    /// Seconds on the clock at the start of a run.
+    pub start_secs: f32,
+    /// Seconds added by every pickup.
+    pub bonus_secs: f32,
+    /// `PickupSpread` at the start of a run.
+    pub start_spread: f32,
+    /// Seconds into the run until pickups spread over the whole arena.
+    pub full_spread_secs: f32,
+}
+
+impl Default for TimeAttackSettings {
+    fn default() -> Self {
+        Self {
+            start_secs: 30.,
+            bonus_secs: 3.,
+            start_spread: 0.3,
+            full_spread_secs: 90.,
+        }
+    }
+}
+
+/// The run being played.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimeAttack {
+    /// Seconds left on the clock.
+    pub remaining: f32,
+    /// Seconds since the start of the run.
+    pub elapsed: f32,
+    pub pickups: u32,
+}
+
+impl Default for TimeAttack {
+    fn default() -> Self {
+        Self::new(&TimeAttackSettings::default())
+    }
+}
+
+impl TimeAttack {
+    pub fn new(settings: &TimeAttackSettings) -> Self {
+        Self {
+            remaining: settings.start_secs,
+            elapsed: 0.,
+            pickups: 0,
+        }
+    }
+
+    pub fn pickups_per_minute(&self) -> f32 {
+        if self.elapsed > 0. {
+            self.pickups as f32 * 60. / self.elapsed
+        } else {
+            0.
+        }
+    }
+}
+
+/// Contents of the best-run file.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Longest run, in seconds.
+    pub secs: f32,
+    pub pickups_per_minute: f32,
+}
+
+/// Best run of one game.
+pub struct BestRun {
+    path: PathBuf,
+    record: RunRecord,
+}
+
+impl BestRun {
+    /// Reads the best run of `mode`. A missing or unreadable file gives an
+    /// empty record.
+    pub fn load(mode: GameMode) -> Self {
+        let path = record_path(mode);
+        let record = match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("ignoring the best run in {}: {}", path.display(), err);
+                RunRecord::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => RunRecord::default(),
+            Err(err) => {
+                warn!("could not read {}: {}", path.display(), err);
+                RunRecord::default()
+            }
+        };
+        Self { path, record }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(&self.record, Default::default())
+            .map_err(io::Error::other)?;
+        fs::write(&self.path, contents)
+    }
+
+    pub fn record(&self) -> RunRecord {
+        self.record
+    }
+
+    /// Keeps the better of each figure and returns whether `run` lasted
+    /// longer than the best so far.
+    pub fn update(&mut self, run: &TimeAttack) -> bool {
+        let longest = run.elapsed > self.record.secs;
+        if longest {
+            self.record.secs = run.elapsed;
+        }
+        self.record.pickups_per_minute =
+            self.record.pickups_per_minute.max(run.pickups_per_minute());
+        longest
+    }
+}
+
+fn record_path(mode: GameMode) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pac-human")
+        .join(format!("time-attack-{}.ron", mode.name()))
+}
+
+#[derive(Component)]
+pub struct ResultsPanel;
+
+fn add_bonus_time(
+    settings: Res<TimeAttackSettings>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut run: ResMut<TimeAttack>,
+) {
+    let pickups = collision_events.iter().count() as u32;
+    if pickups > 0 {
+        run.pickups += pickups;
+        run.remaining += settings.bonus_secs * pickups as f32;
+    }
+}
+
+fn count_down(time: Res<Time>, mut run: ResMut<TimeAttack>, mut state: ResMut<State<AppState>>) {
+    run.elapsed += time.delta_seconds();
+    run.remaining -= time.delta_seconds();
+    if run.remaining <= 0. {
+        run.remaining = 0.;
+        let _ = state.set(AppState::GameOver);
+    }
+}
+
+fn widen_spread(
+    settings: Res<TimeAttackSettings>,
+    run: Res<TimeAttack>,
+    spread: Option<ResMut<PickupSpread>>,
+) {
+    if let Some(mut spread) = spread {
+        let progress = (run.elapsed / settings.full_spread_secs).min(1.);
+        spread.0 = settings.start_spread + (1. - settings.start_spread) * progress;
+    }
+}
+
+/// Records the run and shows how it went along the bottom of the screen.
+fn show_results(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Res<TimeAttack>,
+    mut best: ResMut<BestRun>,
+) {
+    let longest = best.update(&run);
+    if let Err(err) = best.save() {
+        warn!("could not save the best run: {}", err);
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let record = best.record();
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: bevy::ui::Size::new(Val::Percent(100.), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(20.),
+                    left: Val::Px(0.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(ResultsPanel)
+        .with_children(|parent| {
+            if longest {
+                parent.spawn_bundle(text("NEW BEST TIME!", &font, 40., Color::YELLOW));
+            }
+            parent.spawn_bundle(text(
+                format!(
+                    "Time: {:.1} s   Pickups: {}   {:.1} per minute",
+                    run.elapsed,
+                    run.pickups,
+                    run.pickups_per_minute()
+                ),
+                &font,
+                30.,
+                Color::WHITE,
+            ));
+            parent.spawn_bundle(text(
+                format!(
+                    "Best: {:.1} s   {:.1} per minute",
+                    record.secs, record.pickups_per_minute
+                ),
+                &font,
+                30.,
+                Color::WHITE,
+            ));
+        });
+}
+
+fn reset_time_attack(
+    settings: Res<TimeAttackSettings>,
+    mut run: ResMut<TimeAttack>,
+    spread: Option<ResMut<PickupSpread>>,
+) {
+    *run = TimeAttack::new(&settings);
+    if let Some(mut spread) = spread {
+        spread.0 = settings.start_spread;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn run(elapsed: f32, pickups: u32) -> TimeAttack {
+        TimeAttack {
+            remaining: 0.,
+            elapsed,
+            pickups,
+        }
+    }
+
+    #[test]
+    fn pickups_per_minute_of_a_run() {
+        assert_eq!(run(30., 5).pickups_per_minute(), 10.);
+        // nothing to divide by before the clock starts
+        assert_eq!(run(0., 0).pickups_per_minute(), 0.);
+    }
+
+    #[test]
+    fn best_run_keeps_the_longest_run_and_the_best_rate_apart() {
+        let mut best = BestRun {
+            path: PathBuf::new(),
+            record: RunRecord::default(),
+        };
+        assert!(best.update(&run(60., 10)));
+
+        // shorter, but quicker
+        assert!(!best.update(&run(30., 10)));
+        assert_eq!(
+            best.record(),
+            RunRecord {
+                secs: 60.,
+                pickups_per_minute: 20.,
+            }
+        );
+
+        // longer, but slower
+        assert!(best.update(&run(120., 10)));
+        assert_eq!(
+            best.record(),
+            RunRecord {
+                secs: 120.,
+                pickups_per_minute: 20.,
+            }
+        );
+    }
+
+    #[test]
+    fn the_game_is_over_when_the_clock_runs_out() {
+        let mut app = App::new();
+        app.add_state(AppState::Playing)
+            .init_resource::<Time>()
+            .insert_resource(TimeAttack {
+                remaining: 1.5,
+                elapsed: 0.,
+                pickups: 0,
+            })
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(count_down));
+        let start = Instant::now();
+        let tick = |app: &mut App, secs| {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs(secs));
+            app.update();
+            *app.world.resource::<State<AppState>>().current()
+        };
+
+        assert_eq!(tick(&mut app, 0), AppState::Playing);
+        assert_eq!(tick(&mut app, 1), AppState::Playing);
+        assert_eq!(tick(&mut app, 2), AppState::GameOver);
+        let run = app.world.resource::<TimeAttack>();
+        assert_eq!(run.remaining, 0.);
+        assert_eq!(run.elapsed, 2.);
+    }
+}
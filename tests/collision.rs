@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use pac_human::collision::{
+    block_fox, check_for_collisions, check_for_collisions_with_fox, keep_fox_in_arena,
+    snake_eating, CollisionEvent, GrowthEvent, ARENA_SIZE,
+};
+use pac_human::components::{Collider, Enemy, Food, Fruit, Obstacle, Player, Points, Size};
+use pac_human::grid::{Direction, Position, PreviousPosition};
+use pac_human::level::Level;
+use pac_human::lives::{check_for_ghost_contact, PlayerDied};
+use pac_human::power::{
+    eat_frightened_ghosts, Frightened, GhostEatenEvent, PowerPelletEaten, PowerState,
+};
+use pac_human::scoring::{ScoreBoard, ScoringPlugin};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugin(ScoringPlugin)
+        .add_event::<CollisionEvent>()
+        .add_event::<GrowthEvent>()
+        .add_event::<PowerPelletEaten>();
+    app
+}
+
+fn collision_count(app: &App) -> usize {
+    let events = app.world.resource::<Events<CollisionEvent>>();
+    events.get_reader().iter(events).count()
+}
+
+fn food_at(app: &mut App, translation: Vec3) -> Entity {
+    app.world
+        .spawn()
+        .insert(Food)
+        .insert(Points(10))
+        .insert(Transform::from_translation(translation))
+        .insert(Size::square(20.))
+        .id()
+}
+
+#[test]
+fn pacman_eats_the_food_it_touches() {
+    let mut app = app();
+    app.add_system(check_for_collisions);
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Transform::default());
+    let touching = food_at(&mut app, Vec3::new(10., 0., 0.));
+    let far = food_at(&mut app, Vec3::new(500., 0., 0.));
+
+    app.update();
+
+    assert!(app.world.get_entity(touching).is_none());
+    assert!(app.world.get_entity(far).is_some());
+    assert_eq!(collision_count(&app), 1);
+}
+
+#[test]
+fn snake_eats_the_food_on_its_cell() {
+    let mut app = app();
+    app.add_system(snake_eating);
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Position { x: 3, y: 3 });
+    let eaten = app
+        .world
+        .spawn()
+        .insert(Food)
+        .insert(Fruit)
+        .insert(Points(100))
+        .insert(Position { x: 3, y: 3 })
+        .id();
+    let next = app
+        .world
+        .spawn()
+        .insert(Food)
+        .insert(Points(10))
+        .insert(Position { x: 4, y: 3 })
+        .id();
+
+    app.update();
+    app.update();
+
+    assert!(app.world.get_entity(eaten).is_none());
+    assert!(app.world.get_entity(next).is_some());
+    let scoreboard = app.world.resource::<ScoreBoard>();
+    assert_eq!(scoreboard.score, 100);
+    assert_eq!(scoreboard.fruit, 1);
+}
+
+#[test]
+fn fox_scores_the_cubes_it_touches() {
+    let mut app = app();
+    app.add_system(check_for_collisions_with_fox);
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Transform::default())
+        .insert(Size(Vec3::new(25., 70., 125.)));
+    let cube = app
+        .world
+        .spawn()
+        .insert(Collider)
+        .insert(Points(1))
+        .insert(Transform::from_xyz(0., 50., 60.))
+        .insert(Size::square(100.))
+        .id();
+    app.world
+        .spawn()
+        .insert(Collider)
+        .insert(Points(1))
+        .insert(Transform::from_xyz(1000., 50., 1000.))
+        .insert(Size::square(100.));
+
+    app.update();
+    app.update();
+
+    assert!(app.world.get_entity(cube).is_none());
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 1);
+}
+
+#[test]
+fn fox_facing_diagonally_collides_along_its_body() {
+    let mut app = app();
+    app.add_system(check_for_collisions_with_fox);
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Transform::from_rotation(Quat::from_rotation_y(
+            std::f32::consts::FRAC_PI_4,
+        )))
+        .insert(Size(Vec3::new(25., 70., 125.)));
+    let cube_at = |app: &mut App, x: f32, z: f32| {
+        app.world
+            .spawn()
+            .insert(Collider)
+            .insert(Points(1))
+            .insert(Transform::from_xyz(x, 50., z))
+            .insert(Size::square(20.))
+            .id()
+    };
+    // along the fox's body toward its tail, and off to its side
+    let along = cube_at(&mut app, 40., 40.);
+    let beside = cube_at(&mut app, 40., -40.);
+
+    app.update();
+    app.update();
+
+    assert!(app.world.get_entity(along).is_none());
+    assert!(app.world.get_entity(beside).is_some());
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 1);
+}
+
+/// A fox facing along z, 25 wide and 125 long.
+fn fox_at(app: &mut App, x: f32, z: f32) -> Entity {
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Transform::from_xyz(x, 0., z))
+        .insert(Size(Vec3::new(25., 70., 125.)))
+        .id()
+}
+
+fn fox_ground(app: &App, fox: Entity) -> Vec2 {
+    let translation = app.world.get::<Transform>(fox).unwrap().translation;
+    Vec2::new(translation.x, translation.z)
+}
+
+#[test]
+fn fox_slides_along_a_wall_it_walks_into_at_an_angle() {
+    let mut app = app();
+    app.add_system(block_fox);
+    // its face on the right is at x = 20
+    app.world
+        .spawn()
+        .insert(Obstacle)
+        .insert(Transform::from_xyz(0., 50., 0.))
+        .insert(Size(Vec3::new(40., 100., 400.)));
+    let fox = fox_at(&mut app, 35., 0.);
+
+    for _ in 0..3 {
+        app.world.get_mut::<Transform>(fox).unwrap().translation += Vec3::new(-5., 0., 5.);
+        app.update();
+    }
+
+    // held against the wall on x, and free to go on along z
+    let ground = fox_ground(&app, fox);
+    assert!((ground - Vec2::new(20. + 12.5, 15.)).length() < 1e-4);
+}
+
+#[test]
+fn fox_is_kept_in_the_arena_at_every_edge() {
+    let mut app = app();
+    app.add_system(keep_fox_in_arena);
+    let fox = fox_at(&mut app, 0., 0.);
+    let edge = ARENA_SIZE / 2.;
+
+    for (outside, clamped) in [
+        (Vec2::new(edge, 0.), Vec2::new(edge - 12.5, 0.)),
+        (Vec2::new(-edge, 0.), Vec2::new(12.5 - edge, 0.)),
+        (Vec2::new(0., edge), Vec2::new(0., edge - 62.5)),
+        (Vec2::new(0., -edge), Vec2::new(0., 62.5 - edge)),
+    ] {
+        app.world.get_mut::<Transform>(fox).unwrap().translation =
+            Vec3::new(outside.x, 0., outside.y);
+        app.update();
+
+        assert!((fox_ground(&app, fox) - clamped).length() < 1e-4);
+    }
+}
+
+#[test]
+fn fox_is_kept_inside_the_maze() {
+    let mut app = app();
+    // 5 cells of 50 wide and 3 deep
+    app.insert_resource(Level::parse("#####\n#P..#\n#####\n").unwrap())
+        .add_system(keep_fox_in_arena);
+    let fox = fox_at(&mut app, 400., -400.);
+
+    app.update();
+
+    assert!((fox_ground(&app, fox) - Vec2::new(125. - 12.5, 62.5 - 75.)).length() < 1e-4);
+}
+
+/// A snake head that moved from (1, 0) to (2, 0) and a ghost that moved the
+/// other way in the same frame, so that they swapped cells.
+fn head_on(app: &mut App) -> Entity {
+    app.world
+        .spawn()
+        .insert(Player)
+        .insert(Position { x: 2, y: 0 })
+        .insert(PreviousPosition(Position { x: 1, y: 0 }));
+    app.world
+        .spawn()
+        .insert(Enemy)
+        .insert(Direction::Left)
+        .insert(Position { x: 1, y: 0 })
+        .insert(PreviousPosition(Position { x: 2, y: 0 }))
+        .id()
+}
+
+#[test]
+fn snake_eats_a_frightened_ghost_it_crosses_head_on() {
+    let mut app = app();
+    app.insert_resource(Level::parse("P..G\n").unwrap())
+        .init_resource::<PowerState>()
+        .add_event::<GhostEatenEvent>()
+        .add_system(eat_frightened_ghosts);
+    let ghost = head_on(&mut app);
+    app.world.entity_mut(ghost).insert(Frightened);
+
+    app.update();
+    app.update();
+
+    // back in the pen
+    assert_eq!(
+        app.world.get::<Position>(ghost),
+        Some(&Position { x: 3, y: 0 })
+    );
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 200);
+}
+
+#[test]
+fn snake_dies_on_a_ghost_it_crosses_head_on() {
+    let mut app = app();
+    app.add_event::<PlayerDied>()
+        .add_system(check_for_ghost_contact);
+    head_on(&mut app);
+
+    app.update();
+
+    let died = app.world.resource::<Events<PlayerDied>>();
+    assert_eq!(died.iter_current_update_events().count(), 1);
+}
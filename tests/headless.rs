@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use pac_human::actions::Action;
+use pac_human::bonus_fruit::BonusFruitSettings;
+use pac_human::components::{BonusFruit, Collider, Enemy, Fruit, Pellet, Player, Points};
+use pac_human::grid::Position;
+use pac_human::headless::{headless_app, IdleAgent, RandomWalkAgent};
+use pac_human::level::{Level, Tile, Wall};
+use pac_human::lives::{Lives, STARTING_LIVES};
+use pac_human::play_area::PlayArea;
+use pac_human::scoring::ScoreBoard;
+use pac_human::snake::{SnakeSegment, TailMode};
+use pac_human::spawning::FruitSettings;
+use pac_human::speed::{FrightenedSpeed, Speed, SpeedSettings};
+use pac_human::state::AppState;
+use pac_human::GameMode;
+
+fn count<T: Component>(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<T>>()
+        .iter(&app.world)
+        .count()
+}
+
+fn run(app: &mut App, ticks: u32) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+#[test]
+fn idle_snake_leaves_the_pellets_alone() {
+    let mut app = headless_app(GameMode::Packman2, 1, IdleAgent);
+    run(&mut app, 120);
+
+    let pellets = app
+        .world
+        .resource::<Level>()
+        .cells()
+        .filter(|(_, tile)| *tile == Tile::Pellet)
+        .count();
+    assert!(pellets > 0);
+    assert_eq!(count::<Pellet>(&mut app), pellets);
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 0);
+}
+
+#[test]
+fn snake_eats_the_pellets_it_walks_over() {
+    // five pellets lie between the start of level 1 and the wall on its left
+    let mut app = headless_app(GameMode::Packman2, 1, |_: &mut World| {
+        vec![Action::MoveLeft]
+    });
+    run(&mut app, 120);
+
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 50);
+}
+
+#[test]
+fn snake_grows_a_segment_for_every_pellet() {
+    let mut app = headless_app(GameMode::Packman2, 1, |_: &mut World| {
+        vec![Action::MoveLeft]
+    });
+    app.insert_resource(TailMode::Growing);
+    run(&mut app, 120);
+
+    // 10 for each of the five pellets, plus 10 for every segment on each bite
+    assert_eq!(count::<SnakeSegment>(&mut app), 5);
+    assert_eq!(app.world.resource::<ScoreBoard>().score, 50 + 150);
+}
+
+#[test]
+fn fruit_lands_on_free_cells_up_to_the_limit() {
+    let mut app = headless_app(GameMode::Packman2, 9, |_: &mut World| {
+        vec![Action::MoveLeft]
+    });
+    app.insert_resource(FruitSettings {
+        max_on_board: 2,
+        ..default()
+    });
+
+    let mut landed = HashSet::new();
+    let mut most = 0;
+    for _ in 0..900 {
+        app.update();
+        let fruit: Vec<(Entity, Position)> = app
+            .world
+            .query_filtered::<(Entity, &Position), With<Fruit>>()
+            .iter(&app.world)
+            .map(|(entity, pos)| (entity, *pos))
+            .collect();
+        let actors: Vec<Position> = app
+            .world
+            .query_filtered::<&Position, Or<(With<Player>, With<Enemy>)>>()
+            .iter(&app.world)
+            .copied()
+            .collect();
+        let player = *app
+            .world
+            .query_filtered::<&Position, With<Player>>()
+            .single(&app.world);
+        let reachable = app.world.resource::<Level>().reachable_from(player);
+        assert!(fruit.len() <= 2);
+        for (i, (entity, pos)) in fruit.iter().enumerate() {
+            assert!(reachable.contains(pos));
+            assert!(fruit[..i].iter().all(|(_, other)| other != pos));
+            // actors may walk over it later, but it never lands under one
+            if landed.insert(*entity) {
+                assert!(!actors.contains(pos));
+            }
+        }
+        most = most.max(fruit.len());
+    }
+    assert_eq!(most, 2);
+}
+
+#[test]
+fn bonus_fruit_shows_up_at_the_start_and_runs_out() {
+    let mut app = headless_app(GameMode::Packman2, 1, |_: &mut World| {
+        vec![Action::MoveLeft]
+    });
+    app.insert_resource(BonusFruitSettings {
+        pellets: vec![3],
+        secs: 5.,
+    });
+    run(&mut app, 120);
+
+    let start = app.world.resource::<Level>().player_start().unwrap();
+    let bonus: Vec<(BonusFruit, Position, u32)> = app
+        .world
+        .query::<(&BonusFruit, &Position, &Points)>()
+        .iter(&app.world)
+        .map(|(fruit, pos, points)| (*fruit, *pos, points.0))
+        .collect();
+    assert_eq!(bonus, vec![(BonusFruit::Cherry, start, 100)]);
+
+    run(&mut app, 300);
+
+    assert_eq!(count::<BonusFruit>(&mut app), 0);
+}
+
+#[test]
+fn speed_settings_are_tuned_not_replaced() {
+    let mut app = headless_app(GameMode::Packman2, 1, IdleAgent);
+    app.insert_resource(SpeedSettings {
+        snake: 2.,
+        frightened: 1.,
+        ..default()
+    });
+    run(&mut app, 200);
+
+    let snake = app
+        .world
+        .query_filtered::<&Speed, With<Player>>()
+        .single(&app.world);
+    assert_eq!(*snake, Speed(2.));
+    let ghost = app
+        .world
+        .query_filtered::<&FrightenedSpeed, With<Enemy>>()
+        .iter(&app.world)
+        .next()
+        .copied();
+    assert_eq!(ghost, Some(FrightenedSpeed(1.)));
+    assert_eq!(app.world.resource::<SpeedSettings>().snake, 2.);
+}
+
+#[test]
+fn pacman_survives_thousands_of_random_ticks() {
+    let mut app = headless_app(GameMode::Pacman, 7, RandomWalkAgent::new(7, 30));
+    let area = *app.world.resource::<PlayArea>();
+
+    let mut farthest: f32 = 0.;
+    for _ in 0..3000 {
+        app.update();
+        let pacman = app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(&app.world)
+            .translation;
+        // a step past the edge at most, before `warp_pacman` brings it round
+        assert!(pacman.x.abs() <= area.width / 2. + 10.);
+        assert!(pacman.y.abs() <= area.height / 2. + 10.);
+        farthest = farthest.max(pacman.truncate().length());
+    }
+
+    // the agent's input reached pacman
+    assert!(farthest > 100.);
+    assert!(matches!(
+        app.world.resource::<State<AppState>>().current(),
+        AppState::Playing | AppState::Dying | AppState::GameOver
+    ));
+    assert_eq!(count::<Player>(&mut app), 1);
+    assert_eq!(count::<Enemy>(&mut app), 10);
+}
+
+/// Cells of the fruit and ghosts on the board, in spawn order.
+fn spawned_positions(app: &mut App) -> Vec<Position> {
+    let mut spawned: Vec<(Entity, Position)> = app
+        .world
+        .query_filtered::<(Entity, &Position), Or<(With<Fruit>, With<Enemy>)>>()
+        .iter(&app.world)
+        .map(|(entity, pos)| (entity, *pos))
+        .collect();
+    spawned.sort_by_key(|(entity, _)| *entity);
+    spawned.into_iter().map(|(_, pos)| pos).collect()
+}
+
+#[test]
+fn same_seed_spawns_the_same_fruit_and_ghosts() {
+    let play = || {
+        let mut app = headless_app(GameMode::Packman2, 5, RandomWalkAgent::new(5, 20));
+        run(&mut app, 900);
+        spawned_positions(&mut app)
+    };
+
+    let first = play();
+    assert!(first.len() > 4);
+    assert_eq!(play(), first);
+}
+
+/// Where every enemy is, in spawn order.
+fn enemy_translations(app: &mut App) -> Vec<Vec3> {
+    app.world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+#[test]
+fn pacman_enemies_roam_the_field() {
+    let mut app = headless_app(GameMode::Pacman, 7, IdleAgent);
+    run(&mut app, 1);
+    let start = enemy_translations(&mut app);
+
+    run(&mut app, 60);
+
+    let now = enemy_translations(&mut app);
+    assert_eq!(now.len(), start.len());
+    for (before, after) in start.iter().zip(&now) {
+        assert_ne!(before, after);
+    }
+}
+
+#[test]
+fn fox_always_has_one_cube_to_collect() {
+    let mut app = headless_app(GameMode::Fox, 3, RandomWalkAgent::new(3, 20));
+    run(&mut app, 1000);
+
+    assert_eq!(count::<Player>(&mut app), 1);
+    assert_eq!(count::<Collider>(&mut app), 1);
+}
+
+#[test]
+fn ghosts_catch_a_fox_standing_still_in_the_maze() {
+    let mut app = headless_app(GameMode::FoxMaze, 5, IdleAgent);
+    run(&mut app, 60);
+
+    let walls = app
+        .world
+        .resource::<Level>()
+        .cells()
+        .filter(|(_, tile)| *tile == Tile::Wall)
+        .count();
+    assert_eq!(count::<Wall>(&mut app), walls);
+    assert_eq!(app.world.resource::<Lives>().0, STARTING_LIVES);
+
+    run(&mut app, 2400);
+
+    assert!(app.world.resource::<Lives>().0 < STARTING_LIVES);
+}
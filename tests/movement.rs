@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use pac_human::components::Player;
+use pac_human::grid::{Direction, Position};
+use pac_human::level::Level;
+use pac_human::movement::warp_pacman;
+use pac_human::play_area::PlayArea;
+
+/// Where pacman ends up after one `warp_pacman` from `start` in an 800×600
+/// area.
+fn warp(start: Vec3) -> Vec3 {
+    let mut app = App::new();
+    app.insert_resource(PlayArea {
+        width: 800.,
+        height: 600.,
+    })
+    .add_system(warp_pacman);
+    let pacman = app
+        .world
+        .spawn()
+        .insert(Player)
+        .insert(Transform::from_translation(start))
+        .id();
+
+    app.update();
+
+    app.world.get::<Transform>(pacman).unwrap().translation
+}
+
+#[test]
+fn pacman_wraps_across_every_edge() {
+    assert_eq!(warp(Vec3::new(405., 0., 0.)), Vec3::new(-395., 0., 0.));
+    assert_eq!(warp(Vec3::new(-405., 0., 0.)), Vec3::new(395., 0., 0.));
+    assert_eq!(warp(Vec3::new(0., 305., 0.)), Vec3::new(0., -295., 0.));
+    assert_eq!(warp(Vec3::new(0., -305., 0.)), Vec3::new(0., 295., 0.));
+}
+
+#[test]
+fn pacman_inside_the_area_stays_put() {
+    assert_eq!(warp(Vec3::new(399., -299., 0.)), Vec3::new(399., -299., 0.));
+}
+
+/// Where a grid actor leaving the left end of the middle row of `edges`
+/// ends up.
+fn step_left(edges: &str) -> (Position, bool) {
+    let map = format!("{}\n#.#\nT.P\n#.#\n", edges);
+    let level = Level::parse(&map).unwrap();
+    let next = level.step(Position { x: 0, y: 1 }, Direction::Left);
+    (next, level.is_wall(next))
+}
+
+#[test]
+fn grid_edges_follow_the_level_policy() {
+    assert_eq!(step_left("edges: wrap"), (Position { x: 2, y: 1 }, false));
+    assert_eq!(
+        step_left("edges: tunnels"),
+        (Position { x: 2, y: 1 }, false)
+    );
+    assert_eq!(step_left("edges: solid"), (Position { x: -1, y: 1 }, true));
+
+    // without a tunnel, only wrap leads round
+    let level = Level::parse("edges: tunnels\n#.P\n").unwrap();
+    assert!(level.is_wall(level.step(Position { x: 1, y: 0 }, Direction::Up)));
+}